@@ -0,0 +1,378 @@
+use crate::activity::ActivityModel;
+use crate::dim_sig_dig::*;
+use crate::substance::*;
+
+use std::collections::HashMap;
+
+// 水のイオン積 (25 ℃)
+pub const PKW: f64 = 14.00;
+// Debye–Hückelの定数 (25 ℃の水, (mol/L)^-1/2)
+pub const DEBYE_HUCKEL_A: f64 = 0.509;
+
+// 酸・塩基としての性質
+// 酸解離の段階ごとのpKaと, プロトンがすべてついた形の電荷で表す
+// 強酸・強塩基は解離しきっているので対イオンの電荷だけを持つ (HClならCl⁻, NaOHならNa⁺)
+#[derive(Debug, Clone, PartialEq)]
+pub struct AcidBase {
+    // 最も酸性の段階から順に
+    pka: Vec<f64>,
+    // プロトンがすべてついた形の電荷 (酢酸なら0, アンモニウムなら+1)
+    charge: i32,
+    // 1 molあたりの対イオンの電荷の合計
+    counter_charge: i32,
+    // dpKa/dT (K⁻¹). Trisなら-0.028
+    temperature_coefficient: f64,
+}
+
+impl AcidBase {
+    pub fn new(pka: Vec<f64>, charge: i32, counter_charge: i32) -> Self {
+        assert!(pka.windows(2).all(|w| w[0] <= w[1]), "pKa must be in ascending order");
+        Self {
+            pka,
+            charge,
+            counter_charge,
+            temperature_coefficient: 0.0,
+        }
+    }
+    // HCl, H2SO4 (2段とも強酸とみなす) など
+    pub fn strong_acid(protons: u32) -> Self {
+        Self::new(vec![], 0, -(protons as i32))
+    }
+    // NaOH, Ba(OH)2 など
+    pub fn strong_base(hydroxides: u32) -> Self {
+        Self::new(vec![], 0, hydroxides as i32)
+    }
+    // 酢酸, リン酸など (中性の分子として加える)
+    pub fn weak_acid(pka: &[f64]) -> Self {
+        Self::new(pka.to_vec(), 0, 0)
+    }
+    // アンモニアなど. pKaは共役酸のもの
+    pub fn weak_base(pka_of_conjugate_acid: &[f64]) -> Self {
+        let n = pka_of_conjugate_acid.len() as i32;
+        Self::new(pka_of_conjugate_acid.to_vec(), n, 0)
+    }
+    // pKbで与える塩基. 共役酸のpKaは pKw - pKb (段の順番は逆になる)
+    pub fn weak_base_from_pkb(pkb: &[f64]) -> Self {
+        let pka: Vec<f64> = pkb.iter().rev().map(|pkb| PKW - pkb).collect();
+        Self::weak_base(&pka)
+    }
+    // 塩として加えるときの対イオン (酢酸ナトリウムなら+1)
+    pub fn set_counter_charge(self, counter_charge: i32) -> Self {
+        Self {
+            counter_charge,
+            ..self
+        }
+    }
+    pub fn set_temperature_coefficient(self, temperature_coefficient: f64) -> Self {
+        Self {
+            temperature_coefficient,
+            ..self
+        }
+    }
+    // temperature (℃) とイオン強度 (mol/L) で補正した濃度基準のpKa
+    // イオン強度はDavies式: HAᶻ ⇌ H⁺ + Aᶻ⁻¹ で pKa' = pKa + (2z - 1) A (√I/(1 + √I) - 0.3I)
+    // pKwの温度変化は無視する
+    pub fn corrected(&self, temperature: f64, ionic_strength: f64) -> Self {
+        // 1価のイオンの -log γ
+        let davies = -ActivityModel::Davies.log_coefficient(1, 0.0, ionic_strength);
+        let pka = self
+            .pka
+            .iter()
+            .enumerate()
+            .map(|(i, pka)| {
+                let z = (self.charge - i as i32) as f64;
+                pka + self.temperature_coefficient * (temperature - 25.0) + (2.0 * z - 1.0) * davies
+            })
+            .collect();
+        Self { pka, ..self.clone() }
+    }
+    pub fn get_pka(&self) -> &[f64] {
+        &self.pka
+    }
+    pub fn get_charge(&self) -> i32 {
+        self.charge
+    }
+    pub fn get_counter_charge(&self) -> i32 {
+        self.counter_charge
+    }
+    // pHでの各化学種の割合 (プロトンの多い形から順に, 和は1)
+    pub fn get_fractions(&self, ph: f64) -> Vec<f64> {
+        // log10(Ka1…Ki / [H⁺]^i)
+        let mut log_terms = vec![0.0];
+        for (i, pka) in self.pka.iter().enumerate() {
+            log_terms.push(log_terms[i] - pka + ph);
+        }
+        let max = log_terms.iter().cloned().fold(f64::MIN, f64::max);
+        let terms: Vec<f64> = log_terms.iter().map(|t| 10_f64.powf(t - max)).collect();
+        let sum: f64 = terms.iter().sum();
+        terms.into_iter().map(|t| t / sum).collect()
+    }
+    // 1 molあたりの電荷 (対イオンを含む)
+    fn mean_charge(&self, ph: f64) -> f64 {
+        let species: f64 = self
+            .get_fractions(ph)
+            .iter()
+            .enumerate()
+            .map(|(i, alpha)| alpha * (self.charge - i as i32) as f64)
+            .sum();
+        self.counter_charge as f64 + species
+    }
+}
+
+// 電荷収支 [H⁺] - [OH⁻] + Σ c z = 0 をpHについて解く
+// components: (酸塩基, mol/L). 左辺はpHについて単調に減るので二分法で求まる
+pub(crate) fn solve_ph(components: &[(AcidBase, f64)]) -> f64 {
+    let balance = |ph: f64| {
+        let h = 10_f64.powf(-ph);
+        let oh = 10_f64.powf(ph - PKW);
+        h - oh + components.iter().map(|(ab, c)| c * ab.mean_charge(ph)).sum::<f64>()
+    };
+    let (mut low, mut high) = (-2.0, PKW + 2.0);
+    for _ in 0..100 {
+        let mid = (low + high) / 2.0;
+        if balance(mid) > 0.0 {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) / 2.0
+}
+
+// 溶液の酸塩基平衡
+// 物質収支 (各物質の全濃度) と電荷収支から[H⁺]を決め, 化学種の分布を出す
+#[derive(Debug, Clone)]
+pub struct Equilibrium {
+    components: Vec<(String, AcidBase, Concentration)>,
+    ph: f64,
+    h: Concentration,
+}
+
+impl Equilibrium {
+    pub fn of(solution: &Solution) -> Self {
+        Self::at(solution, 25.0, 0.0)
+    }
+    // pKaを温度 (℃) とイオン強度 (mol/L) で補正して解く
+    pub fn at(solution: &Solution, temperature: f64, ionic_strength: f64) -> Self {
+        let volume = solution.get_volume();
+        let components: Vec<(String, AcidBase, Concentration)> = solution
+            .get_substances()
+            .into_iter()
+            .filter_map(|(s, n)| {
+                let ab = s.get_acid_base()?.corrected(temperature, ionic_strength);
+                Some((s.get_name().to_string(), ab, (n / volume).convert_to_molar()))
+            })
+            .collect();
+        let solve = |shift: Option<(usize, f64)>| {
+            let raw: Vec<(AcidBase, f64)> = components
+                .iter()
+                .enumerate()
+                .map(|(i, (_, ab, c))| {
+                    let c = c.get_molar_num();
+                    match shift {
+                        Some((j, dc)) if i == j => (ab.clone(), c + dc),
+                        _ => (ab.clone(), c),
+                    }
+                })
+                .collect();
+            solve_ph(&raw)
+        };
+        let ph = solve(None);
+        let h_raw = 10_f64.powf(-ph);
+        // 各物質の濃度の不確かさを差分で[H⁺]に伝える
+        let u_h = components
+            .iter()
+            .enumerate()
+            .map(|(i, (_, _, c))| {
+                let u = c.get_uncertainty().get_molar_num();
+                10_f64.powf(-solve(Some((i, u)))) - h_raw
+            })
+            .map(|d| d * d)
+            .sum::<f64>()
+            .sqrt();
+        // [H⁺]の有効数字は濃度の有効数字の少ない方にそろえる
+        let sig_dig = components
+            .iter()
+            .map(|(_, _, c)| c.get_digit().get_sig_dig())
+            .min()
+            .unwrap_or(2);
+        let h = DimSigDig::molar_from(h_raw)
+            .set_sig_dig(sig_dig)
+            .set_uncertainty(u_h);
+        Self { components, ph, h }
+    }
+    // 小数点以下の桁数は[H⁺]の有効数字の数
+    pub fn get_ph(&self) -> NoDim {
+        self.h.to_p_function().expect("[H⁺] is always positive")
+    }
+    pub fn get_h_concentration(&self) -> Concentration {
+        self.h
+    }
+    pub fn get_oh_concentration(&self) -> Concentration {
+        DimSigDig::molar_from(10_f64.powf(self.ph - PKW)).set_sig_dig(self.h.get_digit().get_sig_dig())
+    }
+    // プロトンの多い形から順に各化学種の濃度
+    pub fn get_species(&self, name: &str) -> Option<Vec<Concentration>> {
+        let (_, ab, c) = self.components.iter().find(|(n, _, _)| n == name)?;
+        let species = ab
+            .get_fractions(self.ph)
+            .into_iter()
+            .map(|alpha| *c * DimSigDig::nodim_from(alpha).set_uncertainty(0.0))
+            .collect();
+        Some(species)
+    }
+    pub fn get_speciation(&self) -> HashMap<String, Vec<Concentration>> {
+        self.components
+            .iter()
+            .map(|(name, _, _)| (name.clone(), self.get_species(name).unwrap()))
+            .collect()
+    }
+}
+
+fn to_mol_raw(n: Mol) -> f64 {
+    n.into_same_unit_with(&DimSigDig::mol_from(0.0)).get_raw_num()
+}
+
+fn to_milli_liter_raw(v: Volume) -> f64 {
+    v.into_same_unit_with(&Volume::milli_liter_from(0.0)).get_raw_num()
+}
+
+// 溶液中の酸・塩基の物質量 (mol). 同じ名前のものはまとめる
+fn acid_base_mols(solution: &Solution) -> HashMap<String, (AcidBase, f64)> {
+    solution
+        .get_substances()
+        .into_iter()
+        .filter_map(|(s, n)| {
+            let ab = s.get_acid_base()?.clone();
+            Some((s.get_name().to_string(), (ab, to_mol_raw(n))))
+        })
+        .collect()
+}
+
+// 変色域
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Indicator {
+    name: &'static str,
+    low: f64,
+    high: f64,
+}
+
+impl Indicator {
+    pub fn get_name(&self) -> &'static str {
+        self.name
+    }
+    pub fn get_range(&self) -> (f64, f64) {
+        (self.low, self.high)
+    }
+    fn transition(&self) -> f64 {
+        (self.low + self.high) / 2.0
+    }
+}
+
+pub const INDICATORS: [Indicator; 9] = [
+    Indicator { name: "thymol blue", low: 1.2, high: 2.8 },
+    Indicator { name: "methyl orange", low: 3.1, high: 4.4 },
+    Indicator { name: "bromocresol green", low: 3.8, high: 5.4 },
+    Indicator { name: "methyl red", low: 4.4, high: 6.2 },
+    Indicator { name: "bromothymol blue", low: 6.0, high: 7.6 },
+    Indicator { name: "phenol red", low: 6.8, high: 8.4 },
+    Indicator { name: "phenolphthalein", low: 8.3, high: 10.0 },
+    Indicator { name: "thymolphthalein", low: 9.3, high: 10.5 },
+    Indicator { name: "alizarin yellow R", low: 10.1, high: 12.0 },
+];
+
+// 滴定曲線 (滴下量 mL, pH)
+// 滴定剤と試料の溶液に入っている物質をそのまま混ぜて, 各点で電荷収支を解く
+#[derive(Debug, Clone)]
+pub struct TitrationCurve {
+    points: Vec<(f64, f64)>,
+}
+
+impl TitrationCurve {
+    pub fn simulate(titrant: &Solution, analyte: &Solution, until: Volume, step: Volume) -> Self {
+        let titrant_volume = to_milli_liter_raw(titrant.get_volume());
+        let analyte_volume = to_milli_liter_raw(analyte.get_volume());
+        let (until, step) = (to_milli_liter_raw(until), to_milli_liter_raw(step));
+        assert!(step > 0.0 && until > 0.0);
+        let titrant = acid_base_mols(titrant);
+        let analyte = acid_base_mols(analyte);
+
+        let count = (until / step).round() as usize;
+        let points = (0..=count)
+            .map(|i| {
+                let v = i as f64 * step;
+                let mut mols = analyte.clone();
+                for (name, (ab, n)) in titrant.iter() {
+                    mols.entry(name.clone()).or_insert((ab.clone(), 0.0)).1 +=
+                        n * v / titrant_volume;
+                }
+                let liter = (analyte_volume + v) / 1000.0;
+                let components: Vec<(AcidBase, f64)> =
+                    mols.into_values().map(|(ab, n)| (ab, n / liter)).collect();
+                (v, solve_ph(&components))
+            })
+            .collect();
+        Self { points }
+    }
+    pub fn get_points(&self) -> Vec<(Volume, f64)> {
+        self.points
+            .iter()
+            .map(|(v, ph)| (Volume::milli_liter_from(*v), *ph))
+            .collect()
+    }
+    // 隣りあう点の差分 (mL, pH/mL)
+    pub fn get_first_derivative(&self) -> Vec<(f64, f64)> {
+        derivative(&self.points)
+    }
+    pub fn get_second_derivative(&self) -> Vec<(f64, f64)> {
+        derivative(&self.get_first_derivative())
+    }
+    // 一次微分の絶対値が極大になる点 (二次微分の符号が変わる点を補間する)
+    // 塩基を酸で滴定するときはpHが下がるので, 一次微分の極小を探すことになる
+    // 平均の傾きの10倍より緩いものは当量点とみなさない
+    pub fn get_equivalence_volumes(&self) -> Vec<Volume> {
+        let (first, last) = (self.points[0], self.points[self.points.len() - 1]);
+        let threshold = 10.0 * ((last.1 - first.1) / (last.0 - first.0)).abs();
+        let d1 = self.get_first_derivative();
+        let d2 = self.get_second_derivative();
+        d2.windows(2)
+            .zip(d1.windows(3))
+            .filter(|(w2, w1)| {
+                // 二次微分が一次微分と同じ符号から逆の符号に変わる
+                let slope = w1[1].1;
+                w2[0].1 * slope > 0.0 && w2[1].1 * slope <= 0.0 && slope.abs() > threshold
+            })
+            .map(|(w2, _)| {
+                let ((v0, s0), (v1, s1)) = (w2[0], w2[1]);
+                Volume::milli_liter_from(v0 + (v1 - v0) * s0 / (s0 - s1))
+            })
+            .collect()
+    }
+    // 曲線がpHを横切る滴下量のうち, volumeに最も近いもの
+    fn crossing_near(&self, ph: f64, volume: f64) -> Option<f64> {
+        self.points
+            .windows(2)
+            .filter(|w| (w[0].1 - ph) * (w[1].1 - ph) <= 0.0 && w[0].1 != w[1].1)
+            .map(|w| w[0].0 + (w[1].0 - w[0].0) * (ph - w[0].1) / (w[1].1 - w[0].1))
+            .min_by(|a, b| (a - volume).abs().total_cmp(&(b - volume).abs()))
+    }
+    // 変色するところが当量点に最も近い指示薬
+    pub fn select_indicator(&self, equivalence: Volume) -> Option<Indicator> {
+        let equivalence = to_milli_liter_raw(equivalence);
+        INDICATORS
+            .into_iter()
+            .filter_map(|indicator| {
+                let end_point = self.crossing_near(indicator.transition(), equivalence)?;
+                Some((indicator, (end_point - equivalence).abs()))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(indicator, _)| indicator)
+    }
+}
+
+fn derivative(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    points
+        .windows(2)
+        .map(|w| ((w[0].0 + w[1].0) / 2.0, (w[1].1 - w[0].1) / (w[1].0 - w[0].0)))
+        .collect()
+}
@@ -0,0 +1,94 @@
+use crate::acid_base::DEBYE_HUCKEL_A;
+use crate::dim_sig_dig::*;
+
+// 電離して生じるイオン
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ion {
+    name: String,
+    charge: i32,
+    // 1 molあたりのmol数 (Na2SO4のNa⁺なら2)
+    count: u32,
+    // イオンサイズパラメータ α (pm). 拡張Debye–Hückel式でだけ使う
+    size: f64,
+}
+
+impl Ion {
+    pub fn new(name: &str, charge: i32, count: u32, size: f64) -> Self {
+        assert!(charge != 0, "{} is not an ion", name);
+        assert!(count > 0 && size > 0.0);
+        Self {
+            name: name.to_string(),
+            charge,
+            count,
+            size,
+        }
+    }
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn get_charge(&self) -> i32 {
+        self.charge
+    }
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+    pub fn get_size(&self) -> f64 {
+        self.size
+    }
+}
+
+// 活量係数の式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ActivityModel {
+    // log γ = -A z² √I / (1 + α√I / 305) (I ≦ 0.1 mol/L 程度まで)
+    DebyeHuckel,
+    // log γ = -A z² (√I / (1 + √I) - 0.3I) (I ≦ 0.5 mol/L 程度まで, αによらない)
+    Davies,
+}
+
+impl ActivityModel {
+    // log10 γ. ionic_strengthはmol/L
+    pub(crate) fn log_coefficient(&self, charge: i32, size: f64, ionic_strength: f64) -> f64 {
+        let z2 = (charge * charge) as f64;
+        let sqrt_i = ionic_strength.sqrt();
+        match self {
+            Self::DebyeHuckel => -DEBYE_HUCKEL_A * z2 * sqrt_i / (1.0 + size * sqrt_i / 305.0),
+            Self::Davies => -DEBYE_HUCKEL_A * z2 * (sqrt_i / (1.0 + sqrt_i) - 0.3 * ionic_strength),
+        }
+    }
+    // イオン強度の不確かさは数値微分で伝える
+    pub fn get_coefficient(&self, ion: &Ion, ionic_strength: Concentration) -> NoDim {
+        let i = ionic_strength.get_molar_num();
+        let gamma = |i: f64| 10_f64.powf(self.log_coefficient(ion.charge, ion.size, i.max(0.0)));
+        let u = ionic_strength.get_uncertainty().get_molar_num();
+        let (low, high) = (i * (1.0 - 1e-6), (i * (1.0 + 1e-6)).max(1e-12));
+        let derivative = (gamma(high) - gamma(low)) / (high - low);
+        DimSigDig::nodim_from(gamma(i))
+            .set_sig_dig(ionic_strength.get_digit().get_sig_dig())
+            .set_uncertainty(derivative.abs() * u)
+    }
+}
+
+// a = γ c / c° (c° = 1 mol/L)
+pub fn activity_of(c: Concentration, gamma: NoDim) -> NoDim {
+    let standard = Concentration::molar_from(1.0).set_uncertainty(0.0);
+    (gamma * c / standard).into_same_unit_with(&DimSigDig::nodim_from(0.0))
+}
+
+#[test]
+fn activity_coefficient_test() {
+    // Harrisの表: I = 0.01 mol/L で Na⁺ (α = 450 pm) 0.902, SO4²⁻ (α = 400 pm) 0.660
+    let i = Concentration::molar_from(0.0100).set_sig_dig(3);
+    let sodium = Ion::new("Na⁺", 1, 1, 450.0);
+    let sulfate = Ion::new("SO4²⁻", -2, 1, 400.0);
+    let gamma = ActivityModel::DebyeHuckel.get_coefficient(&sodium, i);
+    assert!((gamma.get_raw_num() - 0.902).abs() < 0.002);
+    let gamma = ActivityModel::DebyeHuckel.get_coefficient(&sulfate, i);
+    assert!((gamma.get_raw_num() - 0.660).abs() < 0.005);
+    // Daviesでは大きさによらず電荷だけで決まる
+    let gamma = ActivityModel::Davies.get_coefficient(&sodium, i);
+    assert!((gamma.get_raw_num() - 0.902).abs() < 0.002);
+    // 無限希釈では1
+    let zero = Concentration::molar_from(0.0);
+    assert_eq!(ActivityModel::Davies.get_coefficient(&sulfate, zero).get_raw_num(), 1.0);
+}
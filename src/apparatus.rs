@@ -1,5 +1,6 @@
 use crate::dim_sig_dig::*;
 use crate::substance::*;
+use crate::monte_carlo::Sampler;
 
 use std::collections::HashMap;
 
@@ -16,6 +17,10 @@ trait HasVolume {
         }
         (v as f64).log10().floor() as usize + 1 + err_digit
     }
+    // 許容誤差を一様分布の半幅とみなした標準不確かさ
+    fn calc_uncertainty(err: f64) -> f64 {
+        err.abs() / 3_f64.sqrt()
+    }
     fn get_volume() -> Volume;
 }
 
@@ -36,6 +41,15 @@ impl<const V: usize> Beaker<V> {
             solution: Solution::new(),
         }
     }
+    // 入れた溶液といっしょに後の器具へ伝わる
+    pub fn set_sampler(self, sampler: Sampler) -> Self {
+        Self {
+            solution: self.solution.set_sampler(sampler),
+        }
+    }
+    pub fn get_solution(&self) -> &Solution {
+        &self.solution
+    }
     pub fn add_solution(mut self, l: Solution) -> Self {
         self.solution.add_solution(l);
         assert!(self.solution.get_volume() <= Self::get_volume());
@@ -72,6 +86,7 @@ impl<const V: usize> HasVolume for VolumetricFlask<V> {
         let sig_dig = Self::calc_sig_dig(V, 0.2);
         DimSigDig::milli_liter_from(V as u32)
             .set_sig_dig(sig_dig)
+            .set_uncertainty(Self::calc_uncertainty(0.2))
     }
 }
 
@@ -81,6 +96,17 @@ impl<const V: usize> VolumetricFlask<V> {
             solution: Solution::new()
         }
     }
+    pub fn set_sampler(self, sampler: Sampler) -> Self {
+        Self {
+            solution: self.solution.set_sampler(sampler),
+        }
+    }
+    pub fn get_capacity() -> Volume {
+        Self::get_volume()
+    }
+    pub fn get_solution(&self) -> &Solution {
+        &self.solution
+    }
     pub fn get_concentration(&self) -> HashMap<String, BasicDimSigDig<1, 0, -3>> {
         self.solution.get_concentration()
     }
@@ -93,17 +119,27 @@ impl<const V: usize> VolumetricFlask<V> {
     pub fn get_mol_by_name(&self, name: &str) -> Mol {
         self.solution.get_mol_by_name(name)
     }
+    pub fn get_mol_of_element(&self, symbol: &str) -> Mol {
+        self.solution.get_mol_of_element(symbol)
+    }
+    pub fn get_concentration_of_element(&self, symbol: &str) -> Concentration {
+        self.solution.get_concentration_of_element(symbol)
+    }
+    pub fn get_mass_concentration_of_element(&self, symbol: &str) -> MassConcentration {
+        self.solution.get_mass_concentration_of_element(symbol)
+    }
     pub fn add_solution(mut self, l: Solution) -> Self {
         self.solution.add_solution(l);
         assert!(self.solution.get_volume() <= Self::get_volume());
         self
     }
     pub fn fillup(mut self) -> Self {
-        self.solution.to_be(Self::get_volume());
+        let v = self.solution.get_sampler().sample(Self::get_volume());
+        self.solution.to_be(v);
         self
     }
     pub fn into_pipette_mut<const U: usize>(&mut self, pipette: &mut Pipette<U>) {
-        let v = Pipette::<U>::get_volume();
+        let v = self.solution.get_sampler().sample(Pipette::<U>::get_volume());
         assert!(v < self.solution.get_volume());
         let s = self.solution.dispense(v);
         pipette.aspirate_solution(s);
@@ -116,6 +152,8 @@ impl<const V: usize> VolumetricFlask<V> {
 
 pub struct Pipette<const V: usize> {
     solution: Option<Solution>,
+    // 保存試薬から直接とるときに使う
+    sampler: Sampler,
 }
 
 impl<const V: usize> HasVolume for Pipette<V> {
@@ -124,17 +162,38 @@ impl<const V: usize> HasVolume for Pipette<V> {
         let sig_dig = Self::calc_sig_dig(V, 0.03);
         DimSigDig::milli_liter_from(V as u32)
             .set_sig_dig(sig_dig)
+            .set_uncertainty(Self::calc_uncertainty(0.03))
     }
 }
 
 impl<const V: usize> Pipette<V> {
     pub fn new() -> Self {
-        Self {solution: None}
+        Self {solution: None, sampler: Sampler::default()}
+    }
+    pub fn set_sampler(self, sampler: Sampler) -> Self {
+        Self { sampler, ..self }
+    }
+    pub fn get_capacity() -> Volume {
+        Self::get_volume()
     }
     fn aspirate_solution(&mut self, mut s: Solution) {
         assert!(self.solution.is_none());
         self.solution = Some(s);
     }
+    pub fn aspirate_reagent_mut(&mut self, reagent: &StockReagent) {
+        let v = self.sampler.sample(Self::get_volume());
+        self.aspirate_solution(reagent.dispense(v).set_sampler(self.sampler.clone()));
+    }
+    pub fn aspirate_reagent(mut self, reagent: &StockReagent) -> Self {
+        self.aspirate_reagent_mut(reagent);
+        self
+    }
+    pub fn into_beaker<const U: usize>(&mut self, beaker: Beaker<U>) -> Beaker<U> {
+        match self.solution.take() {
+            Some(s) => beaker.add_solution(s),
+            None => panic!("you are trying to use empty pipette"),
+        }
+    }
     pub fn into_flask_mut<const U: usize>(&mut self, flask: &mut VolumetricFlask<U>) {
         if let Some(s) = self.solution.take() {
             self.solution = None;
@@ -148,3 +207,152 @@ impl<const V: usize> Pipette<V> {
         flask
     }
 }
+
+// メスシリンダー: 目盛りで任意の体積をはかる
+pub struct MeasuringCylinder<const V: usize> {
+    solution: Option<Solution>,
+    sampler: Sampler,
+}
+
+impl<const V: usize> HasVolume for MeasuringCylinder<V> {
+    fn get_volume() -> Volume {
+        let sig_dig = Self::calc_sig_dig(V, Self::tolerance());
+        DimSigDig::milli_liter_from(V as u32)
+            .set_sig_dig(sig_dig)
+            .set_uncertainty(Self::calc_uncertainty(Self::tolerance()))
+    }
+}
+
+impl<const V: usize> MeasuringCylinder<V> {
+    // 許容誤差は容量の0.5 %
+    fn tolerance() -> f64 {
+        V as f64 * 0.005
+    }
+    pub fn new() -> Self {
+        Self {solution: None, sampler: Sampler::default()}
+    }
+    pub fn set_sampler(self, sampler: Sampler) -> Self {
+        Self { sampler, ..self }
+    }
+    pub fn measure(mut self, reagent: &StockReagent, v: Volume) -> Self {
+        assert!(self.solution.is_none());
+        assert!(v <= Self::get_volume());
+        let v = v
+            .convert_to_milli_liter()
+            .set_uncertainty(Self::calc_uncertainty(Self::tolerance()));
+        self.solution = Some(reagent.dispense(self.sampler.sample(v)).set_sampler(self.sampler.clone()));
+        self
+    }
+    pub fn into_beaker<const U: usize>(&mut self, beaker: Beaker<U>) -> Beaker<U> {
+        match self.solution.take() {
+            Some(s) => beaker.add_solution(s),
+            None => panic!("you are trying to use empty measuring cylinder"),
+        }
+    }
+    pub fn into_flask<const U: usize>(&mut self, flask: VolumetricFlask<U>) -> VolumetricFlask<U> {
+        match self.solution.take() {
+            Some(s) => flask.add_solution(s),
+            None => panic!("you are trying to use empty measuring cylinder"),
+        }
+    }
+}
+
+// ビュレットの許容誤差の等級 (JIS / ISO 385)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuretteClass {
+    A,
+    // A級の2倍
+    B,
+}
+
+// ビュレット: 滴下前後の目盛りの差だけ出す
+pub struct Burette<const V: usize> {
+    class: BuretteClass,
+    solution: Option<Solution>,
+    initial_reading: Option<Volume>,
+    // (滴下前, 滴下後) の読み
+    readings: Vec<(Volume, Volume)>,
+}
+
+impl<const V: usize> HasVolume for Burette<V> {
+    fn get_volume() -> Volume {
+        let tolerance = Self::class_a_tolerance();
+        DimSigDig::milli_liter_from(V as u32)
+            .set_sig_dig(Self::calc_sig_dig(V, tolerance))
+            .set_uncertainty(Self::calc_uncertainty(tolerance))
+    }
+}
+
+impl<const V: usize> Burette<V> {
+    // A級: 10 mLまで0.02 mL, 25 mLまで0.03 mL, それより大きければ0.05 mL
+    fn class_a_tolerance() -> f64 {
+        match V {
+            0..=10 => 0.02,
+            11..=25 => 0.03,
+            _ => 0.05,
+        }
+    }
+    pub fn new(class: BuretteClass) -> Self {
+        Self {
+            class,
+            solution: None,
+            initial_reading: None,
+            readings: vec![],
+        }
+    }
+    pub fn get_class(&self) -> BuretteClass {
+        self.class
+    }
+    pub fn get_tolerance(&self) -> f64 {
+        match self.class {
+            BuretteClass::A => Self::class_a_tolerance(),
+            BuretteClass::B => Self::class_a_tolerance() * 2.0,
+        }
+    }
+    // 目盛りいっぱいの体積. get_volumeと違い等級の許容誤差で桁と不確かさを決める
+    fn get_class_volume(&self) -> Volume {
+        let tolerance = self.get_tolerance();
+        DimSigDig::milli_liter_from(V as u32)
+            .set_sig_dig(Self::calc_sig_dig(V, tolerance))
+            .set_uncertainty(Self::calc_uncertainty(tolerance))
+    }
+    // メスフラスコの溶液を目盛りいっぱいまで入れる
+    pub fn fill_from<const U: usize>(mut self, flask: &mut VolumetricFlask<U>) -> Self {
+        assert!(self.solution.is_none());
+        let v = self.get_class_volume();
+        assert!(v < flask.solution.get_volume());
+        self.solution = Some(flask.solution.dispense(v));
+        self
+    }
+    // 滴下前の目盛りを読む
+    pub fn read_initial(mut self, reading: Volume) -> Self {
+        assert!(reading <= Self::get_volume());
+        self.initial_reading = Some(reading.convert_to_milli_liter());
+        self
+    }
+    // 目盛りの差 (読みの不確かさ2回分と許容誤差を合わせる)
+    pub fn get_delivered_volume(&self, final_reading: Volume) -> Volume {
+        let initial = self.initial_reading.expect("read the burette before titration");
+        assert!(final_reading <= Self::get_volume(), "{} is beyond the scale", final_reading);
+        let delivered = final_reading.convert_to_milli_liter() - initial;
+        assert!(delivered > 0.0.into());
+        let u_tolerance = Self::calc_uncertainty(self.get_tolerance());
+        let u = delivered.get_uncertainty().get_raw_num().hypot(u_tolerance);
+        delivered.set_uncertainty(u)
+    }
+    // 滴下後の目盛りを読むまで入れる. 次の滴定はその目盛りから始める
+    pub fn into_beaker<const U: usize>(&mut self, final_reading: Volume, beaker: Beaker<U>) -> Beaker<U> {
+        let v = self.get_delivered_volume(final_reading);
+        let Some(s) = self.solution.as_mut() else {
+            panic!("you are trying to use empty burette");
+        };
+        assert!(v <= s.get_volume(), "the burette does not have {} left", v);
+        let initial = self.initial_reading.replace(final_reading.convert_to_milli_liter()).unwrap();
+        self.readings.push((initial, final_reading.convert_to_milli_liter()));
+        let v = s.get_sampler().sample(v);
+        beaker.add_solution(s.dispense(v))
+    }
+    pub fn get_readings(&self) -> &[(Volume, Volume)] {
+        &self.readings
+    }
+}
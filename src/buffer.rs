@@ -0,0 +1,230 @@
+use crate::acid_base::*;
+use crate::dim_sig_dig::*;
+use crate::planner::*;
+use crate::substance::*;
+use crate::temperature::Temperature;
+
+#[derive(Debug, Clone)]
+pub enum BufferError {
+    // どちらの成分にもpKaがない
+    NoBufferingSpecies,
+    // 目的のpHがどのpKaからも1以上離れている
+    OutOfRange { ph: f64, pka: Vec<f64> },
+    // 二つの成分の比をどう変えても届かない
+    Unreachable(f64),
+    NoFlask(f64),
+    // 保存溶液だけでメスフラスコの半分を超える
+    TooMuchStock(f64),
+}
+
+impl std::fmt::Display for BufferError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoBufferingSpecies => write!(f, "neither component has a pKa"),
+            Self::OutOfRange { ph, pka } => {
+                write!(f, "pH {} is outside the buffer range of pKa {:?}", ph, pka)
+            }
+            Self::Unreachable(ph) => write!(f, "pH {} cannot be reached with these components", ph),
+            Self::NoFlask(v) => write!(f, "no volumetric flask holds {} mL", v),
+            Self::TooMuchStock(v) => write!(f, "{} mL of stock solutions is too much", v),
+        }
+    }
+}
+
+impl std::error::Error for BufferError {}
+
+#[derive(Debug, Clone)]
+pub enum BufferComponent {
+    // 固体をはかりとる
+    Solid(Substance),
+    // 保存溶液をメスシリンダーではかる
+    Stock(StockReagent),
+}
+
+impl BufferComponent {
+    fn get_substance(&self) -> &Substance {
+        match self {
+            Self::Solid(s) => s,
+            Self::Stock(r) => r.get_substance(),
+        }
+    }
+    // 酸・塩基としての性質がなければ緩衝液にならない
+    fn get_acid_base(&self) -> Result<&AcidBase, BufferError> {
+        self.get_substance()
+            .get_acid_base()
+            .ok_or(BufferError::NoBufferingSpecies)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BufferRecipe {
+    plan: PreparationPlan,
+    ph: NoDim,
+}
+
+impl BufferRecipe {
+    pub fn get_plan(&self) -> &PreparationPlan {
+        &self.plan
+    }
+    pub fn get_steps(&self) -> &[Step] {
+        self.plan.get_steps()
+    }
+    // 手順どおりに作った溶液を平衡のモデルで解いたpH
+    pub fn get_expected_ph(&self) -> NoDim {
+        self.ph
+    }
+    pub fn execute(&self) -> Solution {
+        self.plan.execute()
+    }
+}
+
+// 目的のpH, 全濃度, 体積の緩衝液の作り方を考える
+// acidとbaseの比を平衡のモデルで解き, 天秤やメスシリンダーで扱える量に丸めてから確かめる
+// 弱酸 + 共役塩基 (酢酸 + 酢酸ナトリウム) のほか, 弱酸 + 強塩基, 強酸 + 弱塩基 (HCl + Tris) も扱う
+#[derive(Debug, Clone)]
+pub struct BufferDesigner {
+    acid: BufferComponent,
+    base: BufferComponent,
+    // ℃
+    temperature: f64,
+    // mol/L
+    ionic_strength: f64,
+    planner: Planner,
+}
+
+impl BufferDesigner {
+    pub fn new(acid: BufferComponent, base: BufferComponent) -> Self {
+        Self {
+            acid,
+            base,
+            temperature: 25.0,
+            ionic_strength: 0.0,
+            planner: Planner::new(),
+        }
+    }
+    pub fn set_temperature(self, temperature: Temperature) -> Self {
+        Self {
+            temperature: temperature.get_celsius_num(),
+            ..self
+        }
+    }
+    pub fn set_ionic_strength(self, ionic_strength: Concentration) -> Self {
+        Self {
+            ionic_strength: ionic_strength.get_molar_num(),
+            ..self
+        }
+    }
+    // 天秤の読み取り限度などはPlannerにそろえる
+    pub fn set_planner(self, planner: Planner) -> Self {
+        Self { planner, ..self }
+    }
+    pub fn design(&self, ph: NoDim, total: Concentration, volume: Volume) -> Result<BufferRecipe, BufferError> {
+        let ph = ph.into_same_unit_with(&DimSigDig::nodim_from(0.0)).get_raw_num();
+        let milli_liter = volume
+            .into_same_unit_with(&Volume::milli_liter_from(0.0))
+            .get_raw_num();
+        let flask = FLASK_SIZES
+            .into_iter()
+            .find(|f| *f as f64 >= milli_liter)
+            .ok_or(BufferError::NoFlask(milli_liter))?;
+        let liter = flask as f64 / 1000.0;
+        let n_total = total.get_molar_num() * liter;
+
+        let acid = self.acid.get_acid_base()?.corrected(self.temperature, self.ionic_strength);
+        let base = self.base.get_acid_base()?.corrected(self.temperature, self.ionic_strength);
+        let pka: Vec<f64> = [&acid, &base]
+            .iter()
+            .flat_map(|ab| ab.get_pka().to_vec())
+            .collect();
+        if pka.is_empty() {
+            return Err(BufferError::NoBufferingSpecies);
+        }
+        if pka.iter().all(|pka| (ph - pka).abs() > 1.0) {
+            return Err(BufferError::OutOfRange { ph, pka });
+        }
+        // x: 0 ~ 1 で成分の物質量 (acid, base) を決める
+        let amounts = |x: f64| match (acid.get_pka().is_empty(), base.get_pka().is_empty()) {
+            (false, false) => ((1.0 - x) * n_total, x * n_total),
+            (false, true) => (n_total, x * n_total * acid.get_pka().len() as f64),
+            (true, false) => (x * n_total * base.get_pka().len() as f64, n_total),
+            (true, true) => unreachable!(),
+        };
+        let ph_at = |x: f64| {
+            let (n_acid, n_base) = amounts(x);
+            solve_ph(&[(acid.clone(), n_acid / liter), (base.clone(), n_base / liter)])
+        };
+        let (mut low, mut high) = (0.0, 1.0);
+        let (ph_low, ph_high) = (ph_at(low), ph_at(high));
+        if (ph_low - ph) * (ph_high - ph) > 0.0 {
+            return Err(BufferError::Unreachable(ph));
+        }
+        let rising = ph_high > ph_low;
+        for _ in 0..60 {
+            let mid = (low + high) / 2.0;
+            if (ph_at(mid) < ph) == rising {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        let (n_acid, n_base) = amounts((low + high) / 2.0);
+
+        let mut weigh = vec![];
+        let mut measure = vec![];
+        for (component, n) in [(&self.acid, n_acid), (&self.base, n_base)] {
+            if n <= 0.0 {
+                continue;
+            }
+            match component {
+                BufferComponent::Solid(substance) => {
+                    let mass = DimSigDig::mol_from(n) * substance.get_molar_mass() / substance.get_purity();
+                    let grams = mass.into_same_unit_with(&DimSigDig::gram_from(0.0)).get_raw_num();
+                    weigh.push(Step::Weigh {
+                        substance: substance.clone(),
+                        mass: self.planner.round_mass(grams),
+                    });
+                }
+                BufferComponent::Stock(reagent) => {
+                    let milli_liter = n / reagent.get_concentration().get_molar_num() * 1000.0;
+                    let cylinder = CYLINDER_SIZES
+                        .into_iter()
+                        .find(|c| *c as f64 >= milli_liter)
+                        .ok_or(BufferError::TooMuchStock(milli_liter))?;
+                    measure.push((reagent.clone(), cylinder, milli_liter));
+                }
+            }
+        }
+        let stock_volume: f64 = measure.iter().map(|(_, _, v)| v).sum();
+        let water = flask as f64 / 2.0 - stock_volume;
+        if water <= 0.0 {
+            return Err(BufferError::TooMuchStock(stock_volume));
+        }
+        let beaker = BEAKER_SIZES
+            .into_iter()
+            .find(|b| *b as f64 > flask as f64 / 2.0)
+            .unwrap();
+
+        let mut steps = weigh;
+        steps.push(Step::Dissolve {
+            beaker,
+            volume: Volume::milli_liter_from(water).set_sig_dig(2),
+        });
+        for (reagent, cylinder, milli_liter) in measure {
+            steps.push(Step::Measure {
+                reagent,
+                cylinder,
+                volume: Volume::milli_liter_from(milli_liter).set_sig_dig(3),
+            });
+        }
+        steps.push(Step::TransferToFlask { flask });
+        steps.push(Step::FillUp);
+
+        let buffering = match acid.get_pka().is_empty() {
+            true => &self.base,
+            false => &self.acid,
+        };
+        let plan = PreparationPlan::create(buffering.get_substance().get_name().to_string(), steps);
+        let ph = Equilibrium::at(&plan.execute(), self.temperature, self.ionic_strength).get_ph();
+        Ok(BufferRecipe { plan, ph })
+    }
+}
@@ -0,0 +1,376 @@
+use crate::dim_sig_dig::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalibrationError {
+    // 直線を引くには3点以上いる (残差の自由度 n - 2)
+    TooFewPoints(usize),
+    WeightCountMismatch { points: usize, weights: usize },
+    NonPositiveWeight(f64),
+    // 濃度がすべて同じ
+    NoSpread,
+    ZeroSlope,
+}
+
+impl std::fmt::Display for CalibrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooFewPoints(n) => write!(f, "at least 3 points are needed, but got {}", n),
+            Self::WeightCountMismatch { points, weights } => {
+                write!(f, "{} points but {} weights", points, weights)
+            }
+            Self::NonPositiveWeight(w) => write!(f, "weight must be positive: {}", w),
+            Self::NoSpread => write!(f, "all standards have the same concentration"),
+            Self::ZeroSlope => write!(f, "the calibration line has no slope"),
+        }
+    }
+}
+
+impl std::error::Error for CalibrationError {}
+
+// 検量線 y = a + bx
+// xは濃度 (mol/L), yは吸光度などの無次元の信号
+// 重みは和がnになるように規格化して持つ (重みなしなら全部1)
+#[derive(Debug, Clone)]
+pub struct CalibrationCurve {
+    x: Vec<f64>,
+    y: Vec<f64>,
+    w: Vec<f64>,
+    slope: f64,
+    intercept: f64,
+    // 残差の標準偏差 s(y/x)
+    s_yx: f64,
+}
+
+impl CalibrationCurve {
+    // 最小二乗法
+    pub fn fit(points: &[(Concentration, NoDim)]) -> Result<Self, CalibrationError> {
+        Self::fit_weighted(points, &vec![1.0; points.len()])
+    }
+    // 重みつき最小二乗法. 重みはふつう信号の分散の逆数 1/s²
+    pub fn fit_weighted(
+        points: &[(Concentration, NoDim)],
+        weights: &[f64],
+    ) -> Result<Self, CalibrationError> {
+        let n = points.len();
+        if n < 3 {
+            return Err(CalibrationError::TooFewPoints(n));
+        }
+        if weights.len() != n {
+            return Err(CalibrationError::WeightCountMismatch {
+                points: n,
+                weights: weights.len(),
+            });
+        }
+        if let Some(w) = weights.iter().find(|w| !w.is_finite() || **w <= 0.0) {
+            return Err(CalibrationError::NonPositiveWeight(*w));
+        }
+        let sum_w: f64 = weights.iter().sum();
+        let w: Vec<f64> = weights.iter().map(|w| w * n as f64 / sum_w).collect();
+        let x: Vec<f64> = points.iter().map(|(c, _)| c.get_molar_num()).collect();
+        let y: Vec<f64> = points.iter().map(|(_, s)| s.get_raw_num()).collect();
+
+        let mut curve = Self {
+            x,
+            y,
+            w,
+            slope: 0.0,
+            intercept: 0.0,
+            s_yx: 0.0,
+        };
+        let sxx = curve.sxx();
+        if sxx <= 0.0 {
+            return Err(CalibrationError::NoSpread);
+        }
+        let (x_mean, y_mean) = (curve.x_mean(), curve.y_mean());
+        let sxy: f64 = curve
+            .iter()
+            .map(|(x, y, w)| w * (x - x_mean) * (y - y_mean))
+            .sum();
+        curve.slope = sxy / sxx;
+        curve.intercept = y_mean - curve.slope * x_mean;
+        if curve.slope == 0.0 {
+            return Err(CalibrationError::ZeroSlope);
+        }
+        let ss_res: f64 = curve
+            .iter()
+            .map(|(x, y, w)| w * (y - curve.predict_signal(x)).powi(2))
+            .sum();
+        curve.s_yx = (ss_res / (n - 2) as f64).sqrt();
+        Ok(curve)
+    }
+    fn iter(&self) -> impl Iterator<Item = (f64, f64, f64)> + '_ {
+        self.x
+            .iter()
+            .zip(self.y.iter())
+            .zip(self.w.iter())
+            .map(|((x, y), w)| (*x, *y, *w))
+    }
+    fn len(&self) -> usize {
+        self.x.len()
+    }
+    fn x_mean(&self) -> f64 {
+        self.iter().map(|(x, _, w)| w * x).sum::<f64>() / self.len() as f64
+    }
+    fn y_mean(&self) -> f64 {
+        self.iter().map(|(_, y, w)| w * y).sum::<f64>() / self.len() as f64
+    }
+    fn sxx(&self) -> f64 {
+        let x_mean = self.x_mean();
+        self.iter().map(|(x, _, w)| w * (x - x_mean).powi(2)).sum()
+    }
+    fn predict_signal(&self, x: f64) -> f64 {
+        self.intercept + self.slope * x
+    }
+    fn se_slope(&self) -> f64 {
+        self.s_yx / self.sxx().sqrt()
+    }
+    fn se_intercept(&self) -> f64 {
+        let sum_wx2: f64 = self.iter().map(|(x, _, w)| w * x * x).sum();
+        self.s_yx * (sum_wx2 / (self.len() as f64 * self.sxx())).sqrt()
+    }
+    // 残差の自由度
+    pub fn get_degrees_of_freedom(&self) -> usize {
+        self.len() - 2
+    }
+    // 傾き (有効数字は標準誤差に合わせる)
+    pub fn get_slope(&self) -> Sensitivity {
+        (DimSigDig::nodim_from(self.slope) / DimSigDig::molar_from(1.0).set_uncertainty(0.0))
+            .set_uncertainty(self.se_slope())
+            .fit_sig_dig_to_uncertainty()
+    }
+    pub fn get_intercept(&self) -> NoDim {
+        DimSigDig::nodim_from(self.intercept)
+            .set_uncertainty(self.se_intercept())
+            .fit_sig_dig_to_uncertainty()
+    }
+    // 傾きの標準誤差
+    pub fn get_slope_standard_error(&self) -> Sensitivity {
+        self.get_slope().get_uncertainty()
+    }
+    pub fn get_intercept_standard_error(&self) -> NoDim {
+        self.get_intercept().get_uncertainty()
+    }
+    pub fn get_residual_standard_deviation(&self) -> NoDim {
+        DimSigDig::nodim_from(self.s_yx)
+    }
+    // 決定係数 (重みつきなら重みつきの平方和で)
+    pub fn get_r_squared(&self) -> f64 {
+        let y_mean = self.y_mean();
+        let ss_tot: f64 = self.iter().map(|(_, y, w)| w * (y - y_mean).powi(2)).sum();
+        let ss_res = self.s_yx.powi(2) * self.get_degrees_of_freedom() as f64;
+        1.0 - ss_res / ss_tot
+    }
+    // 観測値 - 直線の値
+    pub fn get_residuals(&self) -> Vec<NoDim> {
+        self.iter()
+            .map(|(x, y, _)| DimSigDig::nodim_from(y - self.predict_signal(x)))
+            .collect()
+    }
+    // 未知試料の信号 (replicates回の平均) から濃度を求める
+    // s(x0) = s(y/x) / b × √(1/(w0 m) + 1/n + (y0 - ȳ)² / (b² Sxx))
+    // 重みつきのときの未知試料の重み w0 は平均の1とみなす
+    pub fn predict(&self, signal: NoDim, replicates: usize) -> Prediction {
+        assert!(replicates > 0);
+        let y0 = signal.get_raw_num();
+        let x0 = (y0 - self.intercept) / self.slope;
+        let n = self.len() as f64;
+        let b = self.slope;
+        let s_x0 = self.s_yx / b.abs()
+            * (1.0 / replicates as f64
+                + 1.0 / n
+                + (y0 - self.y_mean()).powi(2) / (b * b * self.sxx()))
+            .sqrt();
+        Prediction {
+            x0,
+            s_x0,
+            degrees_of_freedom: self.get_degrees_of_freedom(),
+        }
+    }
+}
+
+// 検量線から求めた未知試料の濃度
+#[derive(Debug, Clone, Copy)]
+pub struct Prediction {
+    x0: f64,
+    s_x0: f64,
+    degrees_of_freedom: usize,
+}
+
+impl Prediction {
+    // 有効数字は標準誤差に合わせる
+    pub fn get_concentration(&self) -> Concentration {
+        DimSigDig::molar_from(self.x0)
+            .set_uncertainty(self.s_x0)
+            .fit_sig_dig_to_uncertainty()
+    }
+    pub fn get_standard_error(&self) -> Concentration {
+        DimSigDig::molar_from(self.s_x0)
+    }
+    // 信頼水準p (0.95など) の区間, t分布で広げる
+    pub fn get_confidence_interval(&self, p: f64) -> (Concentration, Concentration) {
+        assert!(0.0 < p && p < 1.0);
+        let half_width = t_quantile(0.5 + p / 2.0, self.degrees_of_freedom) * self.s_x0;
+        let fit = |x: f64| {
+            DimSigDig::molar_from(x)
+                .set_uncertainty(self.s_x0)
+                .fit_sig_dig_to_uncertainty()
+        };
+        (fit(self.x0 - half_width), fit(self.x0 + half_width))
+    }
+}
+
+// ln Γ(x) (Lanczos近似)
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| sum + c / (x + i as f64 + 1.0));
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+// 正則化不完全ベータ関数 I_x(a, b) (連分数展開)
+fn incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    // 連分数の収束が速い側で計算する
+    if x > (a + 1.0) / (a + b + 2.0) {
+        return 1.0 - incomplete_beta(1.0 - x, b, a);
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln())
+        .exp()
+        / a;
+    let tiny = 1e-300;
+    let (mut c, mut d) = (1.0, 1.0 - (a + b) * x / (a + 1.0));
+    d = 1.0 / if d.abs() < tiny { tiny } else { d };
+    let mut result = d;
+    for m in 1..300 {
+        let m = m as f64;
+        for numerator in [
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        ] {
+            d = 1.0 + numerator * d;
+            d = 1.0 / if d.abs() < tiny { tiny } else { d };
+            c = 1.0 + numerator / c;
+            c = if c.abs() < tiny { tiny } else { c };
+            result *= c * d;
+        }
+        if (c * d - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    front * result
+}
+
+// 自由度nuのt分布の累積分布関数
+fn t_cdf(t: f64, nu: usize) -> f64 {
+    let nu = nu as f64;
+    let tail = 0.5 * incomplete_beta(nu / (nu + t * t), nu / 2.0, 0.5);
+    if t >= 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+// t分布の分位点 (二分法)
+fn t_quantile(p: f64, nu: usize) -> f64 {
+    assert!(0.0 < p && p < 1.0 && nu > 0);
+    if p < 0.5 {
+        return -t_quantile(1.0 - p, nu);
+    }
+    let (mut low, mut high) = (0.0, 1.0);
+    while t_cdf(high, nu) < p {
+        high *= 2.0;
+    }
+    for _ in 0..100 {
+        let mid = (low + high) / 2.0;
+        if t_cdf(mid, nu) < p {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) / 2.0
+}
+
+#[test]
+fn t_quantile_test() {
+    // 表の値
+    assert!((t_quantile(0.975, 1) - 12.706).abs() < 1e-3);
+    assert!((t_quantile(0.975, 5) - 2.571).abs() < 1e-3);
+    assert!((t_quantile(0.95, 10) - 1.812).abs() < 1e-3);
+    assert!((t_quantile(0.025, 30) + 2.042).abs() < 1e-3);
+}
+
+#[test]
+fn calibration_test() {
+    // Miller & Miller の蛍光分析の例 (濃度はµmol/Lに読みかえる)
+    let points: Vec<(Concentration, NoDim)> = [0, 2, 4, 6, 8, 10, 12]
+        .into_iter()
+        .zip([2.1, 5.0, 9.0, 12.6, 17.3, 21.0, 24.7])
+        .map(|(x, y)| (DimSigDig::micro_molar_from(x), DimSigDig::nodim_from(y)))
+        .collect();
+    let curve = CalibrationCurve::fit(&points).unwrap();
+    println!("slope: {}, intercept: {}", curve.get_slope(), curve.get_intercept());
+    assert!((curve.get_slope().get_raw_num() - 1.93e6).abs() < 0.01e6);
+    assert!((curve.get_intercept().get_raw_num() - 1.52).abs() < 0.01);
+    assert!((curve.get_slope_standard_error().get_raw_num() - 0.0409e6).abs() < 0.001e6);
+    assert!((curve.get_intercept_standard_error().get_raw_num() - 0.2950).abs() < 1e-3);
+    assert!((curve.get_residual_standard_deviation().get_raw_num() - 0.4329).abs() < 1e-4);
+    assert!((curve.get_r_squared().sqrt() - 0.9989).abs() < 1e-4);
+    assert_eq!(curve.get_residuals().len(), 7);
+
+    let prediction = curve.predict(DimSigDig::nodim_from(2.9), 1);
+    assert!((prediction.get_concentration().get_molar_num() - 0.72e-6).abs() < 0.01e-6);
+    assert!((prediction.get_standard_error().get_molar_num() - 0.26e-6).abs() < 0.01e-6);
+    let (low, high) = prediction.get_confidence_interval(0.95);
+    assert!(low < prediction.get_concentration() && prediction.get_concentration() < high);
+
+    // 重みがすべて等しければ重みなしと同じ
+    let weighted = CalibrationCurve::fit_weighted(&points, &[2.0; 7]).unwrap();
+    assert!((weighted.get_slope().get_raw_num() - curve.get_slope().get_raw_num()).abs() < 1.0);
+
+    // Miller & Miller の重みつき回帰の例 (µg/mLをµmol/Lに読みかえる). 重みは 1/s²
+    // 重みなしでは b = 0.0725, a = 0.0133
+    let points: Vec<(Concentration, NoDim)> = [0, 2, 4, 6, 8, 10]
+        .into_iter()
+        .zip([0.009, 0.158, 0.301, 0.472, 0.577, 0.739])
+        .map(|(x, y)| (DimSigDig::micro_molar_from(x), DimSigDig::nodim_from(y)))
+        .collect();
+    let weights: Vec<f64> = [0.001, 0.004, 0.010, 0.013, 0.017, 0.022]
+        .iter()
+        .map(|s: &f64| s.powi(-2))
+        .collect();
+    let unweighted = CalibrationCurve::fit(&points).unwrap();
+    assert!((unweighted.get_slope().get_raw_num() - 0.0725e6).abs() < 0.0001e6);
+    assert!((unweighted.get_intercept().get_raw_num() - 0.0133).abs() < 1e-4);
+    let weighted = CalibrationCurve::fit_weighted(&points, &weights).unwrap();
+    assert!((weighted.get_slope().get_raw_num() - 0.0738e6).abs() < 0.0001e6);
+    assert!((weighted.get_intercept().get_raw_num() - 0.0091).abs() < 1e-4);
+    assert_eq!(
+        CalibrationCurve::fit(&points[..2]).unwrap_err(),
+        CalibrationError::TooFewPoints(2)
+    );
+}
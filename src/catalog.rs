@@ -0,0 +1,301 @@
+use crate::dim_sig_dig::*;
+use crate::formula::*;
+use crate::substance::*;
+
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum CatalogError {
+    Io(std::io::Error),
+    Syntax(String),
+    UnsupportedFormat(String),
+    Quantity { name: String, error: ParseError },
+    Formula { name: String, error: FormulaError },
+    MissingMolarMass(String),
+    InconsistentMolarMass { name: String, given: MolarMass, from_formula: MolarMass },
+    Duplicate(String),
+}
+
+impl std::fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Syntax(e) => write!(f, "{}", e),
+            Self::UnsupportedFormat(s) => write!(f, "unsupported catalog format: {}", s),
+            Self::Quantity { name, error } => write!(f, "{}: {}", name, error),
+            Self::Formula { name, error } => write!(f, "{}: {}", name, error),
+            Self::MissingMolarMass(name) => write!(f, "{}: neither formula nor molar mass", name),
+            Self::InconsistentMolarMass { name, given, from_formula } => write!(
+                f,
+                "{}: molar mass {} does not match formula ({})",
+                name, given, from_formula
+            ),
+            Self::Duplicate(name) => write!(f, "duplicate name or CAS number: {}", name),
+        }
+    }
+}
+
+impl std::error::Error for CatalogError {}
+
+// ファイル上の1項目
+// 量は有効数字を保つために "392.1 g/mol" のような文字列で書く
+#[derive(Debug, Clone, Deserialize)]
+struct RawEntry {
+    name: String,
+    formula: Option<String>,
+    molar_mass: Option<String>,
+    purity: Option<String>,
+    density: Option<String>,
+    cas: Option<String>,
+    // 結晶水の数
+    #[serde(default)]
+    hydrate: u32,
+    #[serde(default)]
+    aliases: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawCatalog {
+    #[serde(default)]
+    substance: Vec<RawEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CatalogEntry {
+    substance: Substance,
+    density: Option<MassConcentration>,
+    hydrate: u32,
+    aliases: Vec<String>,
+}
+
+impl CatalogEntry {
+    pub fn get_substance(&self) -> Substance {
+        self.substance.clone()
+    }
+    pub fn get_density(&self) -> Option<MassConcentration> {
+        self.density
+    }
+    // 液体試薬では純度を質量分率として扱う
+    pub fn get_stock_reagent(&self) -> Option<StockReagent> {
+        let mass_fraction = self.substance.get_purity();
+        let substance = self
+            .get_substance()
+            .set_purity(DimSigDig::nodim_from(1.0).set_uncertainty(0.0));
+        self.density
+            .map(|density| StockReagent::create(substance, density, mass_fraction))
+    }
+    pub fn get_hydrate(&self) -> u32 {
+        self.hydrate
+    }
+    pub fn get_aliases(&self) -> &[String] {
+        &self.aliases
+    }
+    fn is_called(&self, name: &str) -> bool {
+        let name = name.trim();
+        self.substance.get_name().eq_ignore_ascii_case(name)
+            || self.aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+    }
+    fn parse(raw: RawEntry) -> Result<Self, CatalogError> {
+        let name = raw.name.clone();
+        let quantity_error = |error| CatalogError::Quantity {
+            name: name.clone(),
+            error,
+        };
+        let molar_mass = raw
+            .molar_mass
+            .map(|m| m.parse::<MolarMass>())
+            .transpose()
+            .map_err(quantity_error)?;
+        let formula = raw.formula.map(|formula| {
+            // 結晶水が式に書かれていなければ足す
+            let has_hydrate = formula.contains(['·', '*', '•']);
+            match raw.hydrate {
+                n if n > 0 && !has_hydrate => format!("{}·{}H2O", formula, n),
+                _ => formula,
+            }
+        });
+        let substance = match (formula, molar_mass) {
+            (Some(formula), given) => {
+                let substance = Substance::from_formula(raw.name.clone(), &formula).map_err(
+                    |error| CatalogError::Formula {
+                        name: name.clone(),
+                        error,
+                    },
+                )?;
+                // 手で書いたモル質量の打ち間違いを弾く
+                if let Some(given) = given {
+                    let from_formula = substance.get_molar_mass();
+                    if !given.is_close_to(&from_formula) {
+                        return Err(CatalogError::InconsistentMolarMass {
+                            name,
+                            given,
+                            from_formula,
+                        });
+                    }
+                }
+                substance
+            }
+            (None, Some(molar_mass)) => Substance::create(raw.name.clone(), molar_mass),
+            (None, None) => return Err(CatalogError::MissingMolarMass(name)),
+        };
+        let substance = match raw.cas {
+            Some(cas) => substance.set_cas(cas),
+            None => substance,
+        };
+        let substance = match raw.purity {
+            Some(purity) => match parse_range(&purity) {
+                Some((low, high)) => substance.set_assay_range(
+                    low.parse().map_err(quantity_error)?,
+                    high.parse().map_err(quantity_error)?,
+                ),
+                None => substance.set_purity(purity.parse().map_err(quantity_error)?),
+            },
+            None => substance,
+        };
+        let density = raw
+            .density
+            .map(|d| d.parse::<MassConcentration>())
+            .transpose()
+            .map_err(quantity_error)?;
+        Ok(Self {
+            substance,
+            density,
+            hydrate: raw.hydrate,
+            aliases: raw.aliases,
+        })
+    }
+}
+
+// "98.0–102.0 %" や "98.0~102.0 %" を "98.0 %" と "102.0 %" に分ける
+fn parse_range(s: &str) -> Option<(String, String)> {
+    let (low, high) = s.split_once(['–', '~'])?;
+    let number_end = high
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c.is_whitespace()))
+        .unwrap_or(high.len());
+    let unit = &high[number_end..];
+    Some((format!("{} {}", low.trim(), unit), high.trim().to_string()))
+}
+
+// 試薬の一覧
+// 別名で引いても同じSubstanceが返るので, 溶液の中で同じものとしてまとまる
+#[derive(Debug, Clone, Default)]
+pub struct SubstanceCatalog {
+    entries: Vec<CatalogEntry>,
+}
+
+impl SubstanceCatalog {
+    // 拡張子で形式を選ぶ (.toml, .json)
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, CatalogError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(CatalogError::Io)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml_str(&text),
+            Some("json") => Self::from_json_str(&text),
+            _ => Err(CatalogError::UnsupportedFormat(path.display().to_string())),
+        }
+    }
+    pub fn from_toml_str(s: &str) -> Result<Self, CatalogError> {
+        let raw: RawCatalog = toml::from_str(s).map_err(|e| CatalogError::Syntax(e.to_string()))?;
+        Self::from_raw(raw)
+    }
+    pub fn from_json_str(s: &str) -> Result<Self, CatalogError> {
+        let raw: RawCatalog =
+            serde_json::from_str(s).map_err(|e| CatalogError::Syntax(e.to_string()))?;
+        Self::from_raw(raw)
+    }
+    fn from_raw(raw: RawCatalog) -> Result<Self, CatalogError> {
+        let mut catalog = Self::default();
+        for entry in raw.substance {
+            catalog.insert(CatalogEntry::parse(entry)?)?;
+        }
+        Ok(catalog)
+    }
+    fn insert(&mut self, entry: CatalogEntry) -> Result<(), CatalogError> {
+        let names = std::iter::once(entry.substance.get_name())
+            .chain(entry.aliases.iter().map(|a| a.as_str()));
+        for name in names {
+            if self.find(name).is_some() {
+                return Err(CatalogError::Duplicate(name.to_string()));
+            }
+        }
+        if let Some(cas) = entry.substance.get_cas() {
+            if self.find_by_cas(cas).is_some() {
+                return Err(CatalogError::Duplicate(cas.to_string()));
+            }
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+    // 名前か別名 (大文字小文字は区別しない)
+    pub fn find(&self, name: &str) -> Option<&CatalogEntry> {
+        self.entries.iter().find(|e| e.is_called(name))
+    }
+    pub fn find_by_cas(&self, cas: &str) -> Option<&CatalogEntry> {
+        self.entries
+            .iter()
+            .find(|e| e.substance.get_cas() == Some(cas.trim()))
+    }
+    pub fn get(&self, name: &str) -> Option<Substance> {
+        self.find(name).map(|e| e.get_substance())
+    }
+    pub fn get_by_cas(&self, cas: &str) -> Option<Substance> {
+        self.find_by_cas(cas).map(|e| e.get_substance())
+    }
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[test]
+fn catalog_test() {
+    let toml = r#"
+        [[substance]]
+        name = "Mohr"
+        formula = "(NH4)2Fe(SO4)2"
+        hydrate = 6
+        molar_mass = "392.1 g/mol"
+        purity = "99.5 %"
+        cas = "7783-85-9"
+        aliases = ["Mohr's salt", "ammonium iron(II) sulfate hexahydrate"]
+
+        [[substance]]
+        name = "HCl"
+        formula = "HCl"
+        density = "1.19 g/mL"
+        cas = "7647-01-0"
+    "#;
+    let catalog = SubstanceCatalog::from_toml_str(toml).unwrap();
+    assert_eq!(catalog.len(), 2);
+    let mohr = catalog.get("mohr's salt").unwrap();
+    assert!(mohr.is_same_substance(&catalog.get_by_cas("7783-85-9").unwrap()));
+    assert_eq!(mohr.get_name(), "Mohr");
+    assert!(mohr.get_molar_mass().is_close_to(&"392.13 g/mol".parse().unwrap()));
+    assert!(mohr.get_purity().is_close_to(&0.995.into()));
+
+    let json = r#"{"substance": [{"name": "KHP", "formula": "KHC8H4O4", "molar_mass": "204.2 g/mol"}]}"#;
+    let catalog = SubstanceCatalog::from_json_str(json).unwrap();
+    assert!(catalog.get("khp").unwrap().get_purity().is_close_to(&1.0.into()));
+
+    let assay = r#"{"substance": [{"name": "KHP", "formula": "KHC8H4O4", "purity": "99.95–100.05 %"}]}"#;
+    let khp = SubstanceCatalog::from_json_str(assay).unwrap().get("KHP").unwrap();
+    assert!(khp.get_purity().is_close_to(&1.0.into()));
+    assert!((khp.get_purity().get_relative_uncertainty() - 0.0005 / 3_f64.sqrt()).abs() < 1e-9);
+
+    let typo = r#"{"substance": [{"name": "KHP", "formula": "KHC8H4O4", "molar_mass": "240.2 g/mol"}]}"#;
+    assert!(matches!(
+        SubstanceCatalog::from_json_str(typo),
+        Err(CatalogError::InconsistentMolarMass { .. })
+    ));
+    let duplicate = r#"{"substance": [
+        {"name": "KHP", "molar_mass": "204.2 g/mol"},
+        {"name": "Potassium hydrogen phthalate", "molar_mass": "204.2 g/mol", "aliases": ["KHP"]}
+    ]}"#;
+    assert!(matches!(
+        SubstanceCatalog::from_json_str(duplicate),
+        Err(CatalogError::Duplicate(_))
+    ));
+}
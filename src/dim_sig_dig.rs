@@ -19,6 +19,29 @@ pub struct DimSigDig<
     unit: UnitSystem<N, M, L, T, THETA, I, J>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    InvalidNumber(String),
+    UnknownUnit(String),
+    DimensionMismatch { expected: [i8; 7], found: [i8; 7] },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidNumber(s) => write!(f, "invalid number: {}", s),
+            Self::UnknownUnit(s) => write!(f, "unknown unit: {}", s),
+            Self::DimensionMismatch { expected, found } => write!(
+                f,
+                "dimension mismatch: expected {:?}, found {:?}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
 pub type BasicDimSigDig<const N: i8, const M: i8, const L: i8> = DimSigDig<N, M, L, 0, 0, 0, 0>;
 
 pub type Mol = BasicDimSigDig<1, 0, 0>;
@@ -26,6 +49,25 @@ pub type Mass = BasicDimSigDig<0, 1, 0>;
 pub type Volume = BasicDimSigDig<0, 0, 3>;
 pub type NoDim = BasicDimSigDig<0, 0, 0>;
 pub type MolarMass = BasicDimSigDig<-1, 1, 0>;
+pub type Concentration = BasicDimSigDig<1, 0, -3>;
+pub type MassConcentration = BasicDimSigDig<0, 1, -3>;
+// 検量線の傾き (L/mol)
+pub type Sensitivity = BasicDimSigDig<-1, 0, 3>;
+pub type Length = BasicDimSigDig<0, 0, 1>;
+// モル吸光係数 (L mol⁻¹ cm⁻¹ = 10³ cm² mol⁻¹)
+pub type MolarAbsorptivity = BasicDimSigDig<-1, 0, 2>;
+pub type Time = DimSigDig<0, 0, 0, 1, 0, 0, 0>;
+// 温度差 (K). 温度の読みはtemperature::Temperatureで表す
+pub type TemperatureDifference = DimSigDig<0, 0, 0, 0, 1, 0, 0>;
+pub type Current = DimSigDig<0, 0, 0, 0, 0, 1, 0>;
+// 電気量 (C = A s)
+pub type Charge = DimSigDig<0, 0, 0, 1, 0, 1, 0>;
+// 物質量の変化の速さ (mol/s)
+pub type Rate = DimSigDig<1, 0, 0, -1, 0, 0, 0>;
+// 流量. 単位系は10のべきしか扱えないので表示はcm³/s (= mL/s)
+pub type Flow = DimSigDig<0, 0, 3, -1, 0, 0, 0>;
+// 電位 (V = kg m² s⁻³ A⁻¹)
+pub type Voltage = DimSigDig<0, 1, 2, -3, 0, -1, 0>;
 
 impl<
         const N1: i8,
@@ -40,6 +82,9 @@ impl<
     pub fn get_raw_num(&self) -> f64 {
         self.digit.get_raw_num()
     }
+    pub fn get_digit(&self) -> SigDig {
+        self.digit
+    }
     pub fn is_close_to(&self, other: &Self) -> bool {
         let other = other.into_same_unit_with(&self);
         self.digit.is_close_to(&other.digit)
@@ -63,9 +108,12 @@ impl<
             unit,
         }
     }
+    pub fn is_zero(&self) -> bool {
+        self.digit.is_zero()
+    }
     pub fn normalized(&self) -> Self {
         let mut result = self.clone();
-        if result.digit == 0.0.into() {
+        if result.digit.is_zero() {
             return result;
         }
         let d = result.digit.calc_number_of_digit();
@@ -87,6 +135,81 @@ impl<
             unit: self.unit,
         }
     }
+    // 標準不確かさをこの量と同じ単位で設定する
+    pub fn set_uncertainty<U: Into<f64>>(&self, uncertainty: U) -> Self {
+        Self {
+            digit: self.digit.set_uncertainty(uncertainty.into()),
+            unit: self.unit,
+        }
+    }
+    pub fn get_uncertainty(&self) -> Self {
+        Self {
+            digit: SigDig::from(self.digit.get_uncertainty()),
+            unit: self.unit,
+        }
+    }
+    pub fn fit_sig_dig_to_uncertainty(&self) -> Self {
+        Self {
+            digit: self.digit.fit_sig_dig_to_uncertainty(),
+            unit: self.unit,
+        }
+    }
+    pub fn shift_by_uncertainty(&self, k: f64) -> Self {
+        Self {
+            digit: self.digit.shift_by_uncertainty(k),
+            unit: self.unit,
+        }
+    }
+    pub fn get_relative_uncertainty(&self) -> f64 {
+        self.digit.get_relative_uncertainty()
+    }
+    pub fn format_with_uncertainty(&self) -> String {
+        let mut digit = self.digit;
+        digit.pow10(self.unit.get_pow10coe() as i32);
+        let unit = self.unit.pow10(-self.unit.get_pow10coe());
+        format!("{} [{}]", digit.format_with_uncertainty(), unit)
+    }
+}
+
+impl Mol {
+    pub fn mol_from<U: Into<f64>>(n: U) -> Self {
+        let digit = SigDig::from(n);
+        let unit = UnitSystem::default();
+        Self { digit, unit }
+    }
+    pub fn milli_mol_from<U: Into<f64>>(n: U) -> Self {
+        let digit = SigDig::from(n);
+        let unit = UnitSystem::default().set_mol_prefix(SIPrefix::Milli);
+        Self { digit, unit }
+    }
+    pub fn micro_mol_from<U: Into<f64>>(n: U) -> Self {
+        let digit = SigDig::from(n);
+        let unit = UnitSystem::default().set_mol_prefix(SIPrefix::Micro);
+        Self { digit, unit }
+    }
+    pub fn nano_mol_from<U: Into<f64>>(n: U) -> Self {
+        let digit = SigDig::from(n);
+        let unit = UnitSystem::default().set_mol_prefix(SIPrefix::Nano);
+        Self { digit, unit }
+    }
+    pub fn convert_to_mol(self) -> Self {
+        Self {
+            digit: self.digit,
+            unit: self.unit.convert_mol_prefix(SIPrefix::NoPrefix),
+        }
+    }
+    pub fn convert_to_milli_mol(self) -> Self {
+        Self {
+            digit: self.digit,
+            unit: self.unit.convert_mol_prefix(SIPrefix::Milli),
+        }
+    }
+    pub fn convert_to_micro_mol(self) -> Self {
+        Self {
+            digit: self.digit,
+            unit: self.unit.convert_mol_prefix(SIPrefix::Micro),
+        }
+    }
 }
 
 impl Mass {
@@ -95,14 +218,58 @@ impl Mass {
         let unit = UnitSystem::default();
         Self { digit, unit }
     }
+    pub fn kilo_gram_from<U: Into<f64>>(m: U) -> Self {
+        let digit = SigDig::from(m);
+        let unit = UnitSystem::default().set_gram_prefix(SIPrefix::Kilo);
+        Self { digit, unit }
+    }
+    pub fn milli_gram_from<U: Into<f64>>(m: U) -> Self {
+        let digit = SigDig::from(m);
+        let unit = UnitSystem::default().set_gram_prefix(SIPrefix::Milli);
+        Self { digit, unit }
+    }
+    pub fn micro_gram_from<U: Into<f64>>(m: U) -> Self {
+        let digit = SigDig::from(m);
+        let unit = UnitSystem::default().set_gram_prefix(SIPrefix::Micro);
+        Self { digit, unit }
+    }
+    pub fn convert_to_gram(self) -> Self {
+        Self {
+            digit: self.digit,
+            unit: self.unit.convert_gram_prefix(SIPrefix::NoPrefix),
+        }
+    }
+    pub fn convert_to_milli_gram(self) -> Self {
+        Self {
+            digit: self.digit,
+            unit: self.unit.convert_gram_prefix(SIPrefix::Milli),
+        }
+    }
 }
 
 impl Volume {
+    pub fn liter_from<U: Into<f64>>(v: U) -> Self {
+        let digit = SigDig::from(v);
+        let unit = UnitSystem::default().set_meter_prefix(SIPrefix::Deci);
+        Self { digit, unit }
+    }
     pub fn milli_liter_from<U: Into<f64>>(v: U) -> Self {
         let digit = SigDig::from(v);
         let unit = UnitSystem::default().set_meter_prefix(SIPrefix::Centi);
         Self { digit, unit }
     }
+    // 1 µL = 1 mm^3
+    pub fn micro_liter_from<U: Into<f64>>(v: U) -> Self {
+        let digit = SigDig::from(v);
+        let unit = UnitSystem::default().set_meter_prefix(SIPrefix::Milli);
+        Self { digit, unit }
+    }
+    pub fn convert_to_micro_liter(self) -> Self {
+        Self {
+            digit: self.digit,
+            unit: self.unit.convert_meter_prefix(SIPrefix::Milli),
+        }
+    }
     pub fn convert_to_milli_liter(self) -> Self {
         Self {
             digit: self.digit,
@@ -132,15 +299,260 @@ impl BasicDimSigDig<1, 0, -3> {
             .set_meter_prefix(SIPrefix::Deci);
         Self {digit, unit}
     }
+    pub fn milli_molar_from<U: Into<f64>>(c: U) -> Self {
+        let digit = SigDig::from(c.into());
+        let unit = UnitSystem::default()
+            .set_mol_prefix(SIPrefix::Milli)
+            .set_meter_prefix(SIPrefix::Deci);
+        Self {digit, unit}
+    }
+    pub fn micro_molar_from<U: Into<f64>>(c: U) -> Self {
+        let digit = SigDig::from(c.into());
+        let unit = UnitSystem::default()
+            .set_mol_prefix(SIPrefix::Micro)
+            .set_meter_prefix(SIPrefix::Deci);
+        Self {digit, unit}
+    }
+    pub fn nano_molar_from<U: Into<f64>>(c: U) -> Self {
+        let digit = SigDig::from(c.into());
+        let unit = UnitSystem::default()
+            .set_mol_prefix(SIPrefix::Nano)
+            .set_meter_prefix(SIPrefix::Deci);
+        Self {digit, unit}
+    }
     pub fn convert_to_molar(self) -> Self {
         Self {
             digit: self.digit,
             unit: self.unit
+                .convert_mol_prefix(SIPrefix::NoPrefix)
+                .convert_meter_prefix(SIPrefix::Deci),
+        }
+    }
+    pub fn convert_to_micro_molar(self) -> Self {
+        Self {
+            digit: self.digit,
+            unit: self.unit
+                .convert_mol_prefix(SIPrefix::Micro)
+                .convert_meter_prefix(SIPrefix::Deci),
+        }
+    }
+    // mol/L単位の数値
+    pub fn get_molar_num(&self) -> f64 {
+        self.into_same_unit_with(&Self::molar_from(0.0)).get_raw_num()
+    }
+    // mol/Lで表した値のp関数 (pH = -log10[H⁺]). 0以下ならNone
+    pub fn to_p_function(self) -> Option<NoDim> {
+        let molar = self.into_same_unit_with(&Self::molar_from(0.0));
+        molar.digit.to_p_function().map(|digit| NoDim {
+            digit,
+            unit: UnitSystem::default(),
+        })
+    }
+    pub fn from_p_function(p: NoDim) -> Self {
+        let p = p.into_same_unit_with(&DimSigDig::nodim_from(0.0));
+        Self {
+            digit: p.digit.invert_p_function(),
+            unit: Self::molar_from(0.0).unit,
+        }
+    }
+}
+
+impl MassConcentration {
+    // 密度にも使う
+    pub fn gram_per_milli_liter_from<U: Into<f64>>(c: U) -> Self {
+        let digit = SigDig::from(c.into());
+        let unit = UnitSystem::default()
+            .set_meter_prefix(SIPrefix::Centi);
+        Self {digit, unit}
+    }
+    pub fn milli_gram_per_liter_from<U: Into<f64>>(c: U) -> Self {
+        let digit = SigDig::from(c.into());
+        let unit = UnitSystem::default()
+            .set_gram_prefix(SIPrefix::Milli)
+            .set_meter_prefix(SIPrefix::Deci);
+        Self {digit, unit}
+    }
+    pub fn convert_to_milli_gram_per_liter(self) -> Self {
+        Self {
+            digit: self.digit,
+            unit: self.unit
+                .convert_gram_prefix(SIPrefix::Milli)
                 .convert_meter_prefix(SIPrefix::Deci),
         }
     }
 }
 
+impl Length {
+    pub fn meter_from<U: Into<f64>>(l: U) -> Self {
+        let digit = SigDig::from(l.into());
+        let unit = UnitSystem::default();
+        Self {digit, unit}
+    }
+    // セルの光路長はふつうcm
+    pub fn centi_meter_from<U: Into<f64>>(l: U) -> Self {
+        let digit = SigDig::from(l.into());
+        let unit = UnitSystem::default()
+            .set_meter_prefix(SIPrefix::Centi);
+        Self {digit, unit}
+    }
+    pub fn milli_meter_from<U: Into<f64>>(l: U) -> Self {
+        let digit = SigDig::from(l.into());
+        let unit = UnitSystem::default()
+            .set_meter_prefix(SIPrefix::Milli);
+        Self {digit, unit}
+    }
+    pub fn convert_to_centi_meter(self) -> Self {
+        Self {
+            digit: self.digit,
+            unit: self.unit.convert_meter_prefix(SIPrefix::Centi),
+        }
+    }
+}
+
+impl MolarAbsorptivity {
+    pub fn liter_per_mol_per_centi_meter_from<U: Into<f64>>(e: U) -> Self {
+        let digit = SigDig::from(e.into());
+        let unit = UnitSystem::default()
+            .set_meter_prefix(SIPrefix::Centi)
+            .pow10(3);
+        Self {digit, unit}
+    }
+    // L mol⁻¹ cm⁻¹単位の数値
+    pub fn get_liter_per_mol_per_centi_meter_num(&self) -> f64 {
+        self.into_same_unit_with(&Self::liter_per_mol_per_centi_meter_from(0.0))
+            .get_raw_num()
+    }
+}
+
+// 分や時間は10の冪で表せないので秒に直して持つ
+impl Time {
+    pub fn second_from<U: Into<f64>>(t: U) -> Self {
+        let digit = SigDig::from(t.into());
+        let unit = UnitSystem::default();
+        Self {digit, unit}
+    }
+    pub fn milli_second_from<U: Into<f64>>(t: U) -> Self {
+        let digit = SigDig::from(t.into());
+        let unit = UnitSystem::default()
+            .set_second_prefix(SIPrefix::Milli);
+        Self {digit, unit}
+    }
+    // 数値だけ秒に直すので, 後からset_uncertaintyするときは秒で与える
+    pub fn minute_from<U: Into<f64>>(t: U) -> Self {
+        Self::second_from(t.into() * 60.0)
+    }
+    pub fn hour_from<U: Into<f64>>(t: U) -> Self {
+        Self::second_from(t.into() * 3600.0)
+    }
+    // 分で読んだ値 ("30.0(5)" など). 有効数字と不確かさも分のまま秒に直す
+    pub fn from_minutes(minutes: NoDim) -> Self {
+        Self::second_from(60.0).set_uncertainty(0.0) * minutes
+    }
+    pub fn from_hours(hours: NoDim) -> Self {
+        Self::second_from(3600.0).set_uncertainty(0.0) * hours
+    }
+    pub fn get_second_num(&self) -> f64 {
+        self.into_same_unit_with(&Self::second_from(0.0)).get_raw_num()
+    }
+    pub fn get_minute_num(&self) -> f64 {
+        self.get_second_num() / 60.0
+    }
+}
+
+// 1 ℃の差は1 K
+impl TemperatureDifference {
+    pub fn kelvin_from<U: Into<f64>>(t: U) -> Self {
+        let digit = SigDig::from(t.into());
+        let unit = UnitSystem::default();
+        Self {digit, unit}
+    }
+    pub fn get_kelvin_num(&self) -> f64 {
+        self.into_same_unit_with(&Self::kelvin_from(0.0)).get_raw_num()
+    }
+}
+
+impl Current {
+    pub fn ampere_from<U: Into<f64>>(i: U) -> Self {
+        let digit = SigDig::from(i.into());
+        let unit = UnitSystem::default();
+        Self {digit, unit}
+    }
+    pub fn milli_ampere_from<U: Into<f64>>(i: U) -> Self {
+        let digit = SigDig::from(i.into());
+        let unit = UnitSystem::default()
+            .set_ampere_prefix(SIPrefix::Milli);
+        Self {digit, unit}
+    }
+    pub fn get_ampere_num(&self) -> f64 {
+        self.into_same_unit_with(&Self::ampere_from(0.0)).get_raw_num()
+    }
+}
+
+impl Charge {
+    pub fn coulomb_from<U: Into<f64>>(q: U) -> Self {
+        let digit = SigDig::from(q.into());
+        let unit = UnitSystem::default();
+        Self {digit, unit}
+    }
+    pub fn get_coulomb_num(&self) -> f64 {
+        self.into_same_unit_with(&Self::coulomb_from(0.0)).get_raw_num()
+    }
+}
+
+impl Rate {
+    pub fn mol_per_second_from<U: Into<f64>>(r: U) -> Self {
+        let digit = SigDig::from(r.into());
+        let unit = UnitSystem::default();
+        Self {digit, unit}
+    }
+    pub fn get_mol_per_second_num(&self) -> f64 {
+        self.into_same_unit_with(&Self::mol_per_second_from(0.0)).get_raw_num()
+    }
+}
+
+impl Flow {
+    pub fn milli_liter_per_second_from<U: Into<f64>>(f: U) -> Self {
+        let digit = SigDig::from(f.into());
+        let unit = UnitSystem::default()
+            .set_meter_prefix(SIPrefix::Centi);
+        Self {digit, unit}
+    }
+    // 数値だけcm^3/sに直すので, 後からset_uncertaintyするときはmL/sで与える
+    pub fn milli_liter_per_minute_from<U: Into<f64>>(f: U) -> Self {
+        Self::milli_liter_per_second_from(f.into() / 60.0)
+    }
+    // mL/minで読んだ値. 有効数字と不確かさもmL/minのままmL/sに直す
+    pub fn from_milli_liter_per_minute(f: NoDim) -> Self {
+        Self::milli_liter_per_second_from(1.0 / 60.0).set_uncertainty(0.0) * f
+    }
+    pub fn get_milli_liter_per_second_num(&self) -> f64 {
+        self.into_same_unit_with(&Self::milli_liter_per_second_from(0.0)).get_raw_num()
+    }
+    pub fn get_milli_liter_per_minute_num(&self) -> f64 {
+        self.get_milli_liter_per_second_num() * 60.0
+    }
+}
+
+impl Voltage {
+    // 質量はkgで数える
+    pub fn volt_from<U: Into<f64>>(e: U) -> Self {
+        let digit = SigDig::from(e.into());
+        let unit = UnitSystem::default()
+            .set_gram_prefix(SIPrefix::Kilo);
+        Self {digit, unit}
+    }
+    pub fn milli_volt_from<U: Into<f64>>(e: U) -> Self {
+        let digit = SigDig::from(e.into());
+        let unit = UnitSystem::default()
+            .set_gram_prefix(SIPrefix::Kilo)
+            .pow10(-3);
+        Self {digit, unit}
+    }
+    pub fn get_volt_num(&self) -> f64 {
+        self.into_same_unit_with(&Self::volt_from(0.0)).get_raw_num()
+    }
+}
+
 impl NoDim {
     pub fn nodim_from<U: Into<f64>>(x: U) -> Self {
         x.into().into()
@@ -164,6 +576,47 @@ impl<
     }
 }
 
+// "0.4019 g", "20.0 mL", "2.562e-4 mol/L" のような表記を読む
+// 有効数字は数値の書き方から決まる
+impl<
+        const N: i8,
+        const M: i8,
+        const L: i8,
+        const T: i8,
+        const THETA: i8,
+        const I: i8,
+        const J: i8,
+    > std::str::FromStr for DimSigDig<N, M, L, T, THETA, I, J>
+{
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        // 数値部分の終わりを探す (指数のeは後ろに数字が続くときだけ)
+        let chars = s.char_indices().collect::<Vec<_>>();
+        let mut end = s.len();
+        for (i, &(index, c)) in chars.iter().enumerate() {
+            let is_exponent = matches!(c, 'e' | 'E')
+                && chars[i + 1..]
+                    .iter()
+                    .map(|(_, c)| *c)
+                    .find(|c| !matches!(c, '+' | '-'))
+                    .is_some_and(|c| c.is_ascii_digit());
+            let is_sign = matches!(c, '+' | '-')
+                && (i == 0 || matches!(chars[i - 1].1, 'e' | 'E'));
+            let is_uncertainty = matches!(c, '(' | ')');
+            if !(c.is_ascii_digit() || c == '.' || is_exponent || is_sign || is_uncertainty) {
+                end = index;
+                break;
+            }
+        }
+        let (number, unit) = s.split_at(end);
+        Ok(Self {
+            digit: number.parse()?,
+            unit: unit.parse()?,
+        })
+    }
+}
+
 impl<
         const N: i8,
         const M: i8,
@@ -192,7 +645,7 @@ impl<
 {
     type Output = Self;
     fn add(self, rhs: Self) -> Self::Output {
-        if self.digit == 0.0.into() {
+        if self.digit.is_zero() {
             let this = self.into_same_unit_with(&rhs);
             Self {
                 digit: this.digit + rhs.digit,
@@ -220,7 +673,7 @@ impl<
 {
     type Output = Self;
     fn sub(self, rhs: Self) -> Self::Output {
-        if self.digit == 0.0.into() {
+        if self.digit.is_zero() {
             let this = self.into_same_unit_with(&rhs);
             Self {
                 digit: this.digit - rhs.digit,
@@ -363,18 +816,19 @@ impl<
     > std::cmp::PartialOrd for DimSigDig<N, M, L, T, THETA, I, J>
 {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        use std::cmp::Ordering;
-        let digit1 = self.digit * 10.0_f64.powi(self.unit.get_pow10coe() as i32).into();
-        let digit2 = other.digit * 10.0_f64.powi(other.unit.get_pow10coe() as i32).into();
-        if digit1 < digit2 {
-            Some(Ordering::Less)
-        } else if digit1 == digit2 {
-            Some(Ordering::Equal)
-        } else if digit1 > digit2 {
-            Some(Ordering::Greater)
-        } else {
-            None
-        }
+        // 有効数字や不確かさは比べない
+        let other = other.into_same_unit_with(self);
+        self.digit.partial_cmp(&other.digit)
     }
 }
 
+
+#[test]
+fn zero_test() {
+    // 計算で出てきた0は不確かさを持つが, 0として扱う
+    let zero = Mass::gram_from(0.0) * DimSigDig::nodim_from(2.0);
+    assert!(zero.is_zero());
+    assert!(zero.normalized().is_zero());
+    let sum = zero + Mass::milli_gram_from(5.0);
+    assert!((sum.into_same_unit_with(&Mass::gram_from(0.0)).get_raw_num() - 0.005).abs() < 1e-12);
+}
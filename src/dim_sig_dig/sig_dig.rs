@@ -1,15 +1,23 @@
 use std::ops;
+use super::ParseError;
 
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub struct SigDig {
     sig_dig: usize,
     num: f64,
+    // 標準不確かさ (numと同じスケール)
+    // Noneのときは有効数字から求める
+    uncertainty: Option<f64>,
 }
 
 impl SigDig {
     pub fn get_raw_num(&self) -> f64 {
         self.num
     }
+    // 有効数字や不確かさによらず値だけを見る
+    pub fn is_zero(&self) -> bool {
+        self.num == 0.0
+    }
     pub fn is_close_to(&self, other: &Self) -> bool {
         let m = self.sig_dig.min(other.sig_dig);
         let this = self.set_sig_dig(m);
@@ -23,9 +31,114 @@ impl SigDig {
     }
     pub fn pow10(&mut self, d: i32) {
         self.num *= 10_f64.powi(d);
+        self.uncertainty = self.uncertainty.map(|u| u * 10_f64.powi(d));
     }
     pub fn set_sig_dig(&self, sig_dig: usize) -> Self {
-        Self {sig_dig, num: self.num}
+        Self {sig_dig, ..*self}
+    }
+    pub fn get_sig_dig(&self) -> usize {
+        self.sig_dig
+    }
+    // pH = -log10[H⁺] のようなp関数
+    // 小数点以下の桁数を元の値の有効数字の数にする (2.5×10⁻⁵ -> 4.60)
+    // 0以下の値には対数がとれないのでNone
+    pub fn to_p_function(self) -> Option<Self> {
+        if self.num.is_nan() || self.num <= 0.0 {
+            return None;
+        }
+        let num = -self.num.log10();
+        let mut p = Self {
+            sig_dig: 0,
+            num,
+            uncertainty: self.uncertainty.map(|u| u / (self.num * std::f64::consts::LN_10)),
+        };
+        p.sig_dig = if num == 0.0 {
+            self.sig_dig
+        } else {
+            (p.calc_number_of_digit() + 1 + self.sig_dig as i32).max(1) as usize
+        };
+        Some(p)
+    }
+    // p関数から元に戻す (有効数字は小数点以下の桁数)
+    pub fn invert_p_function(&self) -> Self {
+        let num = 10_f64.powf(-self.num);
+        Self {
+            sig_dig: (-self.last_sig_dig()).max(1) as usize,
+            num,
+            uncertainty: self.uncertainty.map(|u| u * num * std::f64::consts::LN_10),
+        }
+    }
+    pub fn set_uncertainty(&self, uncertainty: f64) -> Self {
+        Self {
+            uncertainty: Some(uncertainty.abs()),
+            ..*self
+        }
+    }
+    // 明示されていなければ最後の桁の±0.5を一様分布とみなす
+    pub fn get_uncertainty(&self) -> f64 {
+        self.uncertainty.unwrap_or_else(|| {
+            0.5 * 10_f64.powi(self.last_sig_dig()) / 3_f64.sqrt()
+        })
+    }
+    // 不確かさの最初の桁までを有効数字とする
+    pub fn fit_sig_dig_to_uncertainty(&self) -> Self {
+        let u = self.get_uncertainty();
+        if u <= 0.0 || self.num == 0.0 {
+            return *self;
+        }
+        let d = self.calc_number_of_digit() - u.log10().floor() as i32;
+        self.set_sig_dig(d.max(0) as usize + 1)
+    }
+    // 標準不確かさのk倍だけずらす (不確かさと有効数字はそのまま)
+    pub fn shift_by_uncertainty(&self, k: f64) -> Self {
+        Self {
+            num: self.num + k * self.get_uncertainty(),
+            ..*self
+        }
+    }
+    pub fn get_relative_uncertainty(&self) -> f64 {
+        self.get_uncertainty() / self.num.abs()
+    }
+    // 2.562(4)×10⁻⁴ のような簡略表記
+    pub fn format_with_uncertainty(&self) -> String {
+        let u = self.get_uncertainty();
+        if u == 0.0 || !u.is_finite() {
+            return format!("{}", self);
+        }
+        // 不確かさは1桁で表す
+        let mut u_digit = u.log10().floor() as i32;
+        if (u * 10_f64.powi(-u_digit)).round() >= 10.0 {
+            u_digit += 1;
+        }
+        let mut exponent = if self.num == 0.0 {
+            u_digit
+        } else {
+            self.calc_number_of_digit()
+        };
+        // 丸めで桁が繰り上がる場合
+        if (self.num.abs() * 10_f64.powi(-u_digit)).round() >= 10_f64.powi(exponent - u_digit + 1) {
+            exponent += 1;
+        }
+        let u_digit = u_digit.min(exponent);
+        let decimals = (exponent - u_digit) as usize;
+        let mantissa = self.num * 10_f64.powi(-exponent);
+        let u = (u * 10_f64.powi(-u_digit)).round() as i64;
+        let mut result = format!("{:.*}({})", decimals, mantissa, u);
+        if exponent != 0 {
+            let superscript = exponent
+                .to_string()
+                .chars()
+                .map(|c| match c {
+                    '-' => '⁻',
+                    c => "⁰¹²³⁴⁵⁶⁷⁸⁹"
+                        .chars()
+                        .nth(c.to_digit(10).unwrap() as usize)
+                        .unwrap(),
+                })
+                .collect::<String>();
+            result += format!("×10{}", superscript).as_str();
+        }
+        result
     }
     // 一番下の桁
     // 不確かさを含む桁のa.bcd...*10^x
@@ -49,6 +162,10 @@ impl SigDig {
         } else {
             (self.num * 10_f64.powi(-digit)).round()
         } as i64;
+        // 9.996 -> 10.00 のように桁が繰り上がったら一桁上で数え直す
+        if self.num != 0.0 && num.unsigned_abs() >= 10_u64.pow(self.sig_dig as u32) {
+            return (num / 10, digit + 1);
+        }
         (num, digit)
     }
 }
@@ -56,7 +173,8 @@ impl SigDig {
 impl std::fmt::Display for SigDig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let (num, digit) = self.round();
-        let num = format!("{}", num);
+        let sign = if num < 0 { "-" } else { "" };
+        let num = format!("{}", num.unsigned_abs());
         let num = if digit >= 0 {
             num + "0".repeat(digit as usize).as_str()
         } else {
@@ -71,7 +189,7 @@ impl std::fmt::Display for SigDig {
                     + num.as_str()
             }
         };
-        write!(f, "{}", num)
+        write!(f, "{}{}", sign, num)
     }
 }
 
@@ -80,16 +198,74 @@ impl<U: Into<f64>> From<U> for SigDig {
         Self {
             sig_dig: 10,
             num: value.into(),
+            uncertainty: None,
         }
     }
 }
 
+// 書かれた桁から有効数字を読み取る
+// 先頭の0は有効数字に含めず、末尾の0は含める
+impl std::str::FromStr for SigDig {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // 0.4019(2) のような不確かさ付き表記は括弧の中を最後の桁の単位で読む
+        let (s, uncertainty) = match s.trim().split_once('(') {
+            Some((mantissa, rest)) => {
+                let (u, exponent) = rest
+                    .split_once(')')
+                    .ok_or_else(|| ParseError::InvalidNumber(s.to_string()))?;
+                let decimals = mantissa
+                    .split_once('.')
+                    .map(|(_, decimal)| decimal.len())
+                    .unwrap_or(0) as i32;
+                let u: f64 = u
+                    .parse()
+                    .map_err(|_| ParseError::InvalidNumber(s.to_string()))?;
+                (mantissa.to_string() + exponent, Some((u, decimals)))
+            }
+            None => (s.trim().to_string(), None),
+        };
+        let s = s.as_str();
+        let num: f64 = s
+            .parse()
+            .map_err(|_| ParseError::InvalidNumber(s.to_string()))?;
+        let uncertainty = uncertainty.map(|(u, decimals)| {
+            let exponent = s
+                .split_once(['e', 'E'])
+                .map(|(_, e)| e.parse::<i32>().unwrap_or(0))
+                .unwrap_or(0);
+            u * 10_f64.powi(exponent - decimals)
+        });
+        let mantissa = s
+            .split(['e', 'E'])
+            .next()
+            .unwrap_or("")
+            .trim_start_matches(['+', '-']);
+        let digits = mantissa
+            .chars()
+            .filter(|c| c.is_ascii_digit())
+            .collect::<String>();
+        let significant = digits.trim_start_matches('0').len();
+        let sig_dig = if significant == 0 {
+            // 0.00 などは小数点以下の桁数
+            mantissa
+                .split_once('.')
+                .map(|(_, decimal)| decimal.len())
+                .unwrap_or(0)
+                .max(1)
+        } else {
+            significant
+        };
+        Ok(Self { sig_dig, num, uncertainty })
+    }
+}
+
 impl ops::Neg for SigDig {
     type Output = Self;
     fn neg(self) -> Self::Output {
         Self::Output {
-            sig_dig: self.sig_dig,
             num: -self.num,
+            ..self
         }
     }
 }
@@ -101,14 +277,20 @@ impl ops::Add for SigDig {
         let sig_dig = {
             // 一番下の有効桁を大きいほうに合わせる
             let self_last = self.last_sig_dig();
-            let other_last = self.last_sig_dig();
+            let other_last = rhs.last_sig_dig();
             let last_digit = self_last.max(other_last);
-            let result_num_digit = result_num.log10().floor() as i32;
-            (result_num_digit - last_digit).max(0) as usize + 1
+            if result_num == 0.0 {
+                1
+            } else {
+                let result_num_digit = result_num.abs().log10().floor() as i32;
+                (result_num_digit - last_digit).max(0) as usize + 1
+            }
         };
+        let uncertainty = self.get_uncertainty().hypot(rhs.get_uncertainty());
         Self {
             sig_dig,
             num: result_num,
+            uncertainty: Some(uncertainty),
         }
     }
 }
@@ -123,9 +305,13 @@ impl ops::Sub for SigDig {
 impl ops::Mul for SigDig {
     type Output = Self;
     fn mul(self, rhs: Self) -> Self::Output {
+        // 一次の伝播則
+        let uncertainty = (rhs.num * self.get_uncertainty())
+            .hypot(self.num * rhs.get_uncertainty());
         Self {
             sig_dig: self.sig_dig.min(rhs.sig_dig),
             num: self.num * rhs.num,
+            uncertainty: Some(uncertainty),
         }
     }
 }
@@ -133,9 +319,12 @@ impl ops::Mul for SigDig {
 impl ops::Div for SigDig {
     type Output = Self;
     fn div(self, rhs: Self) -> Self::Output {
+        let uncertainty = (self.get_uncertainty() / rhs.num)
+            .hypot(self.num * rhs.get_uncertainty() / rhs.num.powi(2));
         Self {
             sig_dig: self.sig_dig.min(rhs.sig_dig),
             num: self.num / rhs.num,
+            uncertainty: Some(uncertainty),
         }
     }
 }
@@ -145,3 +334,59 @@ impl std::cmp::PartialOrd for SigDig {
         self.num.partial_cmp(&other.num)
     }
 }
+
+#[test]
+fn parse_sig_dig_test() {
+    let cases = [
+        ("0.4019", 4, "0.4019"),
+        ("20.0", 3, "20.0"),
+        ("2.562e-4", 4, "0.0002562"),
+        ("100", 3, "100"),
+        ("0.050", 2, "0.050"),
+    ];
+    for (s, sig_dig, displayed) in cases {
+        let d: SigDig = s.parse().unwrap();
+        assert_eq!(d.sig_dig, sig_dig);
+        assert_eq!(format!("{}", d), displayed);
+    }
+    assert!("1.2.3".parse::<SigDig>().is_err());
+}
+
+#[test]
+fn uncertainty_test() {
+    let a = SigDig::from(2.5623e-4).set_uncertainty(0.004e-4);
+    assert_eq!(a.format_with_uncertainty(), "2.562(4)×10⁻⁴");
+    let b = SigDig::from(9.99987e-6).set_uncertainty(4.7e-9);
+    assert_eq!(b.format_with_uncertainty(), "1.0000(5)×10⁻⁵");
+    assert_eq!(format!("{}", SigDig::from(9.99987e-6).set_sig_dig(3)), "0.0000100");
+    assert_eq!(format!("{}", SigDig::from(-1.09e-2).set_sig_dig(3)), "-0.0109");
+
+    // p関数: 有効数字2桁なら小数点以下2桁
+    let h: SigDig = "2.5e-5".parse().unwrap();
+    assert_eq!(format!("{}", h.to_p_function().unwrap()), "4.60");
+    let ph: SigDig = "11.234".parse().unwrap();
+    assert_eq!(ph.invert_p_function().get_sig_dig(), 3);
+    assert_eq!(format!("{}", SigDig::from(0.5).set_sig_dig(2).to_p_function().unwrap()), "0.30");
+    assert!(SigDig::from(0.0).to_p_function().is_none());
+    assert!(SigDig::from(-1.0e-3).to_p_function().is_none());
+    let b: SigDig = "0.4019(2)".parse().unwrap();
+    assert!((b.get_uncertainty() - 0.0002).abs() < 1e-12);
+    let c: SigDig = "2.562(4)e-4".parse().unwrap();
+    assert!((c.get_uncertainty() - 0.004e-4).abs() < 1e-12);
+    // 和は二乗和, 積は相対不確かさの二乗和
+    let x = SigDig::from(3.0).set_uncertainty(0.3);
+    let y = SigDig::from(4.0).set_uncertainty(0.4);
+    assert!(((x + y).get_uncertainty() - 0.5).abs() < 1e-12);
+    assert!(((x * y).get_relative_uncertainty() - 0.02_f64.sqrt()).abs() < 1e-12);
+    assert!(((x / y).get_relative_uncertainty() - 0.02_f64.sqrt()).abs() < 1e-12);
+}
+
+#[test]
+fn add_last_digit_test() {
+    // 一番下の有効桁は粗いほうに合わせる (左右どちらに置いても同じ)
+    let fine: SigDig = "0.0456".parse().unwrap();
+    let coarse: SigDig = "12.3".parse().unwrap();
+    assert_eq!(format!("{}", fine + coarse), "12.3");
+    assert_eq!(format!("{}", coarse + fine), "12.3");
+    assert!((fine - fine).is_zero());
+}
@@ -1,12 +1,20 @@
 use std::ops;
+use super::ParseError;
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum SIPrefix {
+    Pico,
+    Nano,
+    Micro,
+    Milli,
+    Centi,
+    Deci,
     #[default]
     NoPrefix,
-    Deci,
-    Centi,
-    Milli,
+    Deca,
+    Hecto,
+    Kilo,
+    Mega,
 }
 
 
@@ -16,22 +24,59 @@ impl std::fmt::Display for SIPrefix {
             f,
             "{}",
             match self {
-                Self::NoPrefix => "",
-                Self::Deci => "d",
-                Self::Centi => "c",
+                Self::Pico => "p",
+                Self::Nano => "n",
+                Self::Micro => "µ",
                 Self::Milli => "m",
+                Self::Centi => "c",
+                Self::Deci => "d",
+                Self::NoPrefix => "",
+                Self::Deca => "da",
+                Self::Hecto => "h",
+                Self::Kilo => "k",
+                Self::Mega => "M",
             }
         )
     }
 }
 
 impl SIPrefix {
+    pub const ALL: [SIPrefix; 11] = [
+        Self::Pico,
+        Self::Nano,
+        Self::Micro,
+        Self::Milli,
+        Self::Centi,
+        Self::Deci,
+        Self::NoPrefix,
+        Self::Deca,
+        Self::Hecto,
+        Self::Kilo,
+        Self::Mega,
+    ];
     pub fn get_degree(&self) -> i8 {
         match self {
-            Self::NoPrefix => 0,
-            Self::Deci => -1,
-            Self::Centi => -2,
+            Self::Pico => -12,
+            Self::Nano => -9,
+            Self::Micro => -6,
             Self::Milli => -3,
+            Self::Centi => -2,
+            Self::Deci => -1,
+            Self::NoPrefix => 0,
+            Self::Deca => 1,
+            Self::Hecto => 2,
+            Self::Kilo => 3,
+            Self::Mega => 6,
+        }
+    }
+    pub fn from_degree(degree: i8) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.get_degree() == degree)
+    }
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        match symbol {
+            // キーボードで打ちやすい表記も受け付ける
+            "u" | "μ" => Some(Self::Micro),
+            _ => Self::ALL.into_iter().find(|p| p.to_string() == symbol),
         }
     }
 }
@@ -70,6 +115,10 @@ impl<
         println!("{}", self);
         self
     }
+    pub fn set_mol_prefix(mut self, prefix: SIPrefix) -> Self {
+        self.prefix[0] = prefix;
+        self
+    }
     pub fn set_gram_prefix(mut self, prefix: SIPrefix) -> Self {
         self.prefix[1] = prefix;
         self
@@ -78,8 +127,16 @@ impl<
         self.prefix[2] = prefix;
         self
     }
+    pub fn set_second_prefix(mut self, prefix: SIPrefix) -> Self {
+        self.prefix[3] = prefix;
+        self
+    }
+    pub fn set_ampere_prefix(mut self, prefix: SIPrefix) -> Self {
+        self.prefix[5] = prefix;
+        self
+    }
     pub fn convert_with_prefix(&self, prefix: [SIPrefix; 7]) -> Self {
-        let mut pow10coe = 0;
+        let mut pow10coe = self.pow10coe;
         for ((p1, p2), d) in self.prefix.iter()
             .zip(prefix.iter())
             .zip(Self::get_degree_array())
@@ -91,6 +148,16 @@ impl<
             prefix,
         }
     }
+    pub fn convert_mol_prefix(&self, mol_prefix: SIPrefix) -> Self {
+        let mut prefix = self.prefix;
+        prefix[0] = mol_prefix;
+        self.convert_with_prefix(prefix)
+    }
+    pub fn convert_gram_prefix(&self, gram_prefix: SIPrefix) -> Self {
+        let mut prefix = self.prefix;
+        prefix[1] = gram_prefix;
+        self.convert_with_prefix(prefix)
+    }
     pub fn convert_meter_prefix(&self, meter_prefix: SIPrefix) -> Self {
         let mut prefix = self.prefix;
         prefix[2] = meter_prefix;
@@ -212,6 +279,22 @@ fn take_diff_of_pow10coe() {
     );
 }
 
+#[test]
+fn convert_with_prefix_test() {
+    let micro_liter = BasicUnit::<0, 0, 3>::default()
+        .set_meter_prefix(SIPrefix::Milli);
+    let milli_liter = micro_liter.convert_meter_prefix(SIPrefix::Centi);
+    assert_eq!(milli_liter.get_pow10coe(), -3);
+    let liter = milli_liter.convert_meter_prefix(SIPrefix::Deci);
+    assert_eq!(liter.get_pow10coe(), -6);
+    let micro_molar = BasicUnit::<1, 0, -3>::default()
+        .set_mol_prefix(SIPrefix::Micro)
+        .set_meter_prefix(SIPrefix::Deci);
+    assert_eq!(format!("{}", micro_molar), "µmol dm^-3");
+    assert_eq!(SIPrefix::from_degree(6), Some(SIPrefix::Mega));
+    assert_eq!(SIPrefix::from_degree(-4), None);
+}
+
 
 
 impl<
@@ -246,6 +329,180 @@ impl<
     }
 }
 
+// 単位記号, 接頭語の付く順に(次元, 指数, 既定の接頭語), 10の冪
+type UnitSymbol = (&'static str, &'static [(usize, i8, SIPrefix)], i8);
+
+const UNIT_SYMBOLS: [UnitSymbol; 12] = [
+    ("mol", &[(0, 1, SIPrefix::NoPrefix)], 0),
+    ("g", &[(1, 1, SIPrefix::NoPrefix)], 0),
+    ("m", &[(2, 1, SIPrefix::NoPrefix)], 0),
+    ("s", &[(3, 1, SIPrefix::NoPrefix)], 0),
+    ("K", &[(4, 1, SIPrefix::NoPrefix)], 0),
+    ("A", &[(5, 1, SIPrefix::NoPrefix)], 0),
+    ("cd", &[(6, 1, SIPrefix::NoPrefix)], 0),
+    // L = dm^3
+    ("L", &[(2, 3, SIPrefix::Deci)], 0),
+    // M = mol dm^-3
+    ("M", &[(0, 1, SIPrefix::NoPrefix), (2, -3, SIPrefix::Deci)], 0),
+    ("%", &[], -2),
+    // V = kg m^2 s^-3 A^-1
+    (
+        "V",
+        &[
+            (1, 1, SIPrefix::Kilo),
+            (2, 2, SIPrefix::NoPrefix),
+            (3, -3, SIPrefix::NoPrefix),
+            (5, -1, SIPrefix::NoPrefix),
+        ],
+        0,
+    ),
+    // C = A s
+    ("C", &[(5, 1, SIPrefix::NoPrefix), (3, 1, SIPrefix::NoPrefix)], 0),
+];
+
+fn find_unit_symbol(term: &str) -> Option<(SIPrefix, &'static UnitSymbol)> {
+    // 接頭語なしを優先 (m は milli ではなく meter)
+    if let Some(unit) = UNIT_SYMBOLS.iter().find(|(symbol, _, _)| *symbol == term) {
+        return Some((SIPrefix::NoPrefix, unit));
+    }
+    UNIT_SYMBOLS
+        .iter()
+        .filter(|(_, components, _)| !components.is_empty())
+        .find_map(|unit| {
+            let prefix = term.strip_suffix(unit.0)?;
+            SIPrefix::from_symbol(prefix).map(|prefix| (prefix, unit))
+        })
+}
+
+// cm^3, dm^-3, cm³, dm⁻³ などの指数を切り出す
+fn split_exponent(term: &str) -> Result<(&str, i8), ParseError> {
+    let superscript = |c: char| "⁻⁰¹²³⁴⁵⁶⁷⁸⁹".contains(c);
+    let Some(index) = term.find(|c: char| c == '^' || superscript(c)) else {
+        return Ok((term, 1));
+    };
+    let (symbol, exponent) = term.split_at(index);
+    let exponent = exponent
+        .trim_start_matches('^')
+        .chars()
+        .map(|c| match "⁰¹²³⁴⁵⁶⁷⁸⁹".chars().position(|s| s == c) {
+            Some(d) => char::from(b'0' + d as u8),
+            None if c == '⁻' => '-',
+            None => c,
+        })
+        .collect::<String>();
+    exponent
+        .parse()
+        .map(|exponent| (symbol, exponent))
+        .map_err(|_| ParseError::UnknownUnit(term.to_string()))
+}
+
+// 次元の指数は問わずに単位を読む
+// 同じ次元に異なる接頭語が現れた場合は最初のものに揃えて10の冪に回す
+fn parse_unit(s: &str) -> Result<([i8; 7], [SIPrefix; 7], i8), ParseError> {
+    let mut degree = [0_i8; 7];
+    let mut prefix: [Option<SIPrefix>; 7] = [None; 7];
+    let mut pow10coe = 0_i8;
+
+    let mut sign = 1;
+    let mut term = String::new();
+    let mut terms = Vec::new();
+    for c in s.trim().chars().chain([' ']) {
+        if [' ', '*', '·', '/'].contains(&c) {
+            if !term.is_empty() {
+                terms.push((std::mem::take(&mut term), sign));
+                sign = 1;
+            }
+            if c == '/' {
+                sign = -1;
+            }
+        } else {
+            term.push(c);
+        }
+    }
+
+    for (term, sign) in terms {
+        let (symbol, exponent) = split_exponent(&term)?;
+        let exponent = exponent * sign;
+        let (user_prefix, (_, components, offset)) =
+            find_unit_symbol(symbol).ok_or_else(|| ParseError::UnknownUnit(term.clone()))?;
+        pow10coe += offset * exponent;
+        for (i, &(index, d, default_prefix)) in components.iter().enumerate() {
+            let mut p = default_prefix;
+            if i == 0 && user_prefix != SIPrefix::NoPrefix {
+                // mL -> cm^3 のように接頭語で表せればそうする
+                let q = default_prefix.get_degree() * d + user_prefix.get_degree();
+                match SIPrefix::from_degree(q / d) {
+                    Some(q_prefix) if q % d == 0 => p = q_prefix,
+                    _ => pow10coe += user_prefix.get_degree() * exponent,
+                }
+            }
+            let d = d * exponent;
+            degree[index] += d;
+            match prefix[index] {
+                None => prefix[index] = Some(p),
+                Some(current) => pow10coe += (p.get_degree() - current.get_degree()) * d,
+            }
+        }
+    }
+
+    let prefix = std::array::from_fn(|i| match degree[i] {
+        0 => SIPrefix::NoPrefix,
+        _ => prefix[i].unwrap_or_default(),
+    });
+    Ok((degree, prefix, pow10coe))
+}
+
+impl<
+        const N: i8,
+        const M: i8,
+        const L: i8,
+        const T: i8,
+        const THETA: i8,
+        const I: i8,
+        const J: i8,
+    > std::str::FromStr for UnitSystem<N, M, L, T, THETA, I, J>
+{
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (degree, prefix, pow10coe) = parse_unit(s)?;
+        let expected = Self::get_degree_array();
+        if degree != expected {
+            return Err(ParseError::DimensionMismatch {
+                expected,
+                found: degree,
+            });
+        }
+        Ok(Self { pow10coe, prefix })
+    }
+}
+
+#[test]
+fn parse_unit_test() {
+    let milli_liter: BasicUnit<0, 0, 3> = "mL".parse().unwrap();
+    assert_eq!(milli_liter, BasicUnit::default().set_meter_prefix(SIPrefix::Centi));
+    let micro_liter: BasicUnit<0, 0, 3> = "uL".parse().unwrap();
+    assert_eq!(micro_liter, BasicUnit::default().set_meter_prefix(SIPrefix::Milli));
+    let molar: BasicUnit<1, 0, -3> = "mol/L".parse().unwrap();
+    assert_eq!(molar, BasicUnit::default().set_meter_prefix(SIPrefix::Deci));
+    let micro_molar: BasicUnit<1, 0, -3> = "µM".parse().unwrap();
+    assert_eq!(
+        micro_molar,
+        BasicUnit::default()
+            .set_mol_prefix(SIPrefix::Micro)
+            .set_meter_prefix(SIPrefix::Deci)
+    );
+    let molar_mass: BasicUnit<-1, 1, 0> = "g mol⁻¹".parse().unwrap();
+    assert_eq!(molar_mass, BasicUnit::default());
+    let ratio: BasicUnit<0, 0, 0> = "mL/L".parse().unwrap();
+    assert_eq!(ratio.get_pow10coe(), -3);
+    let milli_volt: UnitSystem<0, 1, 2, -3, 0, -1, 0> = "mV".parse().unwrap();
+    assert_eq!(milli_volt.into_no_prefix().get_pow10coe(), 0);
+    let coulomb: UnitSystem<0, 0, 0, 1, 0, 1, 0> = "C".parse().unwrap();
+    assert_eq!(coulomb, "A s".parse().unwrap());
+    assert!("mL".parse::<BasicUnit<1, 0, -3>>().is_err());
+    assert!("furlong".parse::<BasicUnit<0, 0, 1>>().is_err());
+}
+
 impl<
         const N: i8,
         const M: i8,
@@ -0,0 +1,378 @@
+use crate::dim_sig_dig::*;
+use std::collections::BTreeMap;
+
+mod periodic_table;
+pub use periodic_table::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormulaError {
+    Empty,
+    UnknownElement(String),
+    UnknownIsotope(String),
+    UnbalancedParenthesis,
+    UnexpectedChar(char),
+    // 数が無い, または大きすぎる
+    InvalidNumber(String),
+}
+
+impl std::fmt::Display for FormulaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty formula"),
+            Self::UnknownElement(s) => write!(f, "unknown element: {}", s),
+            Self::UnknownIsotope(s) => write!(f, "unknown isotope: {}", s),
+            Self::UnbalancedParenthesis => write!(f, "unbalanced parenthesis"),
+            Self::UnexpectedChar(c) => write!(f, "unexpected character: {}", c),
+            Self::InvalidNumber(s) => write!(f, "invalid number: {:?}", s),
+        }
+    }
+}
+
+impl std::error::Error for FormulaError {}
+
+// 原子番号と質量数 (Noneは天然の同位体組成)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Atom {
+    pub atomic_number: u8,
+    pub mass_number: Option<u16>,
+}
+
+impl Atom {
+    pub fn get_element(&self) -> &'static Element {
+        find_element_by_number(self.atomic_number).unwrap()
+    }
+    // 原子量 (同位体なら同位体の質量) とその標準不確かさ
+    fn get_raw_mass(&self) -> (f64, f64) {
+        let element = self.get_element();
+        match self.mass_number {
+            Some(a) => element.get_isotope_mass(a).unwrap(),
+            None => (
+                element.get_raw_atomic_weight(),
+                element.get_standard_uncertainty(),
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for Atom {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.mass_number {
+            Some(a) => write!(f, "[{}{}]", a, self.get_element().symbol),
+            None => write!(f, "{}", self.get_element().symbol),
+        }
+    }
+}
+
+// (NH4)2Fe(SO4)2·6H2O, [13C]O2, SO4^2-, MnO4- のような組成式
+// 水和物の区切りは · か *, 電荷は末尾に ^2- や + の形で書く
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    text: String,
+    atoms: BTreeMap<Atom, u32>,
+    charge: i32,
+}
+
+impl Formula {
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+    pub fn get_charge(&self) -> i32 {
+        self.charge
+    }
+    pub fn get_atoms(&self) -> &BTreeMap<Atom, u32> {
+        &self.atoms
+    }
+    // 同位体は元素にまとめる
+    pub fn get_element_counts(&self) -> BTreeMap<&'static str, u32> {
+        let mut counts = BTreeMap::new();
+        for (atom, n) in self.atoms.iter() {
+            *counts.entry(atom.get_element().symbol).or_insert(0) += n;
+        }
+        counts
+    }
+    pub fn get_element_count(&self, symbol: &str) -> u32 {
+        self.get_element_counts().get(symbol).copied().unwrap_or(0)
+    }
+    // 質量分率 (含まれない元素は0)
+    pub fn get_mass_fraction(&self, symbol: &str) -> NoDim {
+        let mass = self
+            .atoms
+            .iter()
+            .filter(|(atom, _)| atom.get_element().symbol == symbol)
+            .map(|(atom, n)| {
+                let (m, u) = atom.get_raw_mass();
+                DimSigDig::molar_mass_from(m * *n as f64).set_uncertainty(u * *n as f64)
+            })
+            .reduce(|a, b| a + b);
+        match mass {
+            Some(mass) => (mass / self.get_molar_mass()).fit_sig_dig_to_uncertainty(),
+            None => 0.0.into(),
+        }
+    }
+    pub fn get_mass_fractions(&self) -> BTreeMap<&'static str, NoDim> {
+        self.get_element_counts()
+            .into_keys()
+            .map(|symbol| (symbol, self.get_mass_fraction(symbol)))
+            .collect()
+    }
+    // 電子の質量は無視する
+    // 同じ元素の原子量の誤差は完全に相関するので個数倍してから二乗和をとる
+    pub fn get_molar_mass(&self) -> MolarMass {
+        let mut mass = 0.0;
+        let mut variance = 0.0;
+        for (atom, n) in self.atoms.iter() {
+            let (m, u) = atom.get_raw_mass();
+            mass += m * *n as f64;
+            variance += (u * *n as f64).powi(2);
+        }
+        let uncertainty = variance.sqrt();
+        DimSigDig::molar_mass_from(mass)
+            .set_uncertainty(uncertainty)
+            .fit_sig_dig_to_uncertainty()
+    }
+}
+
+impl std::fmt::Display for Formula {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl std::str::FromStr for Formula {
+    type Err = FormulaError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim().to_string();
+        let chars = normalize(&text);
+        if chars.is_empty() {
+            return Err(FormulaError::Empty);
+        }
+        let mut parser = Parser { chars, pos: 0 };
+        let mut atoms = BTreeMap::new();
+        loop {
+            // 水和水などの係数
+            let coefficient = parser.number()?.unwrap_or(1);
+            for (atom, n) in parser.groups()? {
+                add_count(&mut atoms, atom, n, coefficient)?;
+            }
+            match parser.peek() {
+                Some('·') | Some('*') | Some('•') => parser.pos += 1,
+                _ => break,
+            }
+        }
+        let charge = parser.charge()?;
+        if let Some(c) = parser.peek() {
+            return Err(match c {
+                ')' | ']' => FormulaError::UnbalancedParenthesis,
+                c => FormulaError::UnexpectedChar(c),
+            });
+        }
+        if atoms.is_empty() {
+            return Err(FormulaError::Empty);
+        }
+        Ok(Self {
+            text,
+            atoms,
+            charge,
+        })
+    }
+}
+
+// 下付き数字を数字に, 上付きの電荷を ^ 表記に直す
+fn normalize(s: &str) -> Vec<char> {
+    let subscript = "₀₁₂₃₄₅₆₇₈₉";
+    let superscript = "⁰¹²³⁴⁵⁶⁷⁸⁹⁺⁻";
+    let mut result = Vec::new();
+    let mut in_superscript = false;
+    for c in s.chars().filter(|c| !c.is_whitespace()) {
+        if let Some(d) = subscript.chars().position(|s| s == c) {
+            result.push(char::from(b'0' + d as u8));
+        } else if let Some(d) = superscript.chars().position(|s| s == c) {
+            if !in_superscript {
+                result.push('^');
+            }
+            result.push(match d {
+                10 => '+',
+                11 => '-',
+                d => char::from(b'0' + d as u8),
+            });
+            in_superscript = true;
+            continue;
+        } else {
+            result.push(c);
+        }
+        in_superscript = false;
+    }
+    result
+}
+
+// 個数の足し算と掛け算はあふれたらエラー
+fn add_count(atoms: &mut BTreeMap<Atom, u32>, atom: Atom, n: u32, times: u32) -> Result<(), FormulaError> {
+    let count = atoms.entry(atom).or_insert(0);
+    *count = n
+        .checked_mul(times)
+        .and_then(|n| count.checked_add(n))
+        .ok_or_else(|| FormulaError::InvalidNumber(format!("{}×{}", n, times)))?;
+    Ok(())
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+    // 数字が無ければNone
+    fn number(&mut self) -> Result<Option<u32>, FormulaError> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let digits = self.chars[start..self.pos].iter().collect::<String>();
+        if digits.is_empty() {
+            return Ok(None);
+        }
+        digits.parse().map(Some).map_err(|_| FormulaError::InvalidNumber(digits))
+    }
+    // 元素記号, 同位体, 括弧の並び
+    fn groups(&mut self) -> Result<BTreeMap<Atom, u32>, FormulaError> {
+        let mut atoms = BTreeMap::new();
+        loop {
+            let group = match self.peek() {
+                Some('(') | Some('[') | Some('{') => {
+                    let open = self.peek().unwrap();
+                    self.pos += 1;
+                    if open == '[' && self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                        self.isotope()?
+                    } else {
+                        let close = match open {
+                            '(' => ')',
+                            '[' => ']',
+                            _ => '}',
+                        };
+                        let inner = self.groups()?;
+                        if self.peek() != Some(close) {
+                            return Err(FormulaError::UnbalancedParenthesis);
+                        }
+                        self.pos += 1;
+                        inner
+                    }
+                }
+                Some(c) if c.is_ascii_uppercase() => self.element()?,
+                _ => break,
+            };
+            let n = self.number()?.unwrap_or(1);
+            for (atom, m) in group {
+                add_count(&mut atoms, atom, m, n)?;
+            }
+        }
+        Ok(atoms)
+    }
+    fn symbol(&mut self) -> String {
+        let mut symbol = String::new();
+        if let Some(c) = self.peek().filter(|c| c.is_ascii_uppercase()) {
+            symbol.push(c);
+            self.pos += 1;
+        }
+        while let Some(c) = self.peek().filter(|c| c.is_ascii_lowercase()) {
+            symbol.push(c);
+            self.pos += 1;
+        }
+        symbol
+    }
+    fn element(&mut self) -> Result<BTreeMap<Atom, u32>, FormulaError> {
+        let symbol = self.symbol();
+        let atom = match symbol.as_str() {
+            // 重水素, 三重水素
+            "D" => Atom { atomic_number: 1, mass_number: Some(2) },
+            "T" => Atom { atomic_number: 1, mass_number: Some(3) },
+            _ => {
+                let element = find_element(&symbol)
+                    .ok_or(FormulaError::UnknownElement(symbol))?;
+                Atom { atomic_number: element.atomic_number, mass_number: None }
+            }
+        };
+        Ok(BTreeMap::from([(atom, 1)]))
+    }
+    // [13C] の形, 開き括弧は読んである
+    fn isotope(&mut self) -> Result<BTreeMap<Atom, u32>, FormulaError> {
+        let mass_number = match self.number()? {
+            Some(a) => u16::try_from(a).map_err(|_| FormulaError::InvalidNumber(a.to_string()))?,
+            None => return Err(FormulaError::InvalidNumber(String::new())),
+        };
+        let symbol = self.symbol();
+        if self.peek() != Some(']') {
+            return Err(FormulaError::UnbalancedParenthesis);
+        }
+        self.pos += 1;
+        let name = format!("{}{}", mass_number, symbol);
+        let element = find_element(&symbol).ok_or(FormulaError::UnknownElement(symbol))?;
+        element
+            .get_isotope_mass(mass_number)
+            .ok_or(FormulaError::UnknownIsotope(name))?;
+        let atom = Atom {
+            atomic_number: element.atomic_number,
+            mass_number: Some(mass_number),
+        };
+        Ok(BTreeMap::from([(atom, 1)]))
+    }
+    // ^2-, ^3+, +, -, +2, 2- (^のあと) など
+    fn charge(&mut self) -> Result<i32, FormulaError> {
+        let caret = self.peek() == Some('^');
+        if caret {
+            self.pos += 1;
+        }
+        let before = self.number()?;
+        let sign = match self.peek() {
+            Some('+') => 1,
+            Some('-') => -1,
+            Some(c) if caret || before.is_some() => return Err(FormulaError::UnexpectedChar(c)),
+            None if caret || before.is_some() => return Err(FormulaError::UnexpectedChar('^')),
+            _ => return Ok(0),
+        };
+        self.pos += 1;
+        let after = if before.is_none() { self.number()? } else { None };
+        let n = before.or(after).unwrap_or(1);
+        let n = i32::try_from(n).map_err(|_| FormulaError::InvalidNumber(n.to_string()))?;
+        Ok(sign * n)
+    }
+}
+
+#[test]
+fn formula_test() {
+    let mohr: Formula = "(NH4)2Fe(SO4)2·6H2O".parse().unwrap();
+    let mohr_star: Formula = "(NH4)2Fe(SO4)2*6H2O".parse().unwrap();
+    assert_eq!(mohr.get_atoms(), mohr_star.get_atoms());
+    let molar_mass = mohr.get_molar_mass();
+    println!("{}", molar_mass.format_with_uncertainty());
+    assert!(molar_mass.is_close_to(&DimSigDig::molar_mass_from(392.13).set_sig_dig(5)));
+
+    let sulfate: Formula = "SO4^2-".parse().unwrap();
+    assert_eq!(sulfate.get_charge(), -2);
+    assert_eq!("SO₄²⁻".parse::<Formula>().unwrap().get_atoms(), sulfate.get_atoms());
+    assert_eq!("MnO4-".parse::<Formula>().unwrap().get_charge(), -1);
+    assert_eq!("Fe+3".parse::<Formula>().unwrap().get_charge(), 3);
+    assert_eq!("K4[Fe(CN)6]".parse::<Formula>().unwrap().get_atoms().values().sum::<u32>(), 17);
+
+    assert_eq!(mohr.get_element_count("H"), 20);
+    assert_eq!(mohr.get_element_count("Fe"), 1);
+    let fe = mohr.get_mass_fraction("Fe");
+    assert!((fe.get_raw_num() - 55.845 / 392.125).abs() < 1e-5);
+    let total: f64 = mohr.get_mass_fractions().values().map(|w| w.get_raw_num()).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+
+    let labeled: Formula = "[13C]O2".parse().unwrap();
+    assert_eq!(labeled.get_element_count("C"), 1);
+    assert!((labeled.get_molar_mass().get_raw_num() - 45.001).abs() < 0.001);
+
+    assert_eq!("Xx2O".parse::<Formula>(), Err(FormulaError::UnknownElement("Xx".to_string())));
+    assert_eq!("(NH4".parse::<Formula>(), Err(FormulaError::UnbalancedParenthesis));
+    assert_eq!("[99C]".parse::<Formula>(), Err(FormulaError::UnknownIsotope("99C".to_string())));
+    // 質量数の無い角括弧はただの括弧
+    assert_eq!("[C]O2".parse::<Formula>().unwrap().get_atoms(), "CO2".parse::<Formula>().unwrap().get_atoms());
+    assert_eq!("[65548C]".parse::<Formula>(), Err(FormulaError::InvalidNumber("65548".to_string())));
+    assert_eq!("C99999999999".parse::<Formula>(), Err(FormulaError::InvalidNumber("99999999999".to_string())));
+    assert!(matches!("(C4000000000)2".parse::<Formula>(), Err(FormulaError::InvalidNumber(_))));
+}
@@ -0,0 +1,158 @@
+use crate::dim_sig_dig::*;
+
+// IUPAC 標準原子量 (簡略値) とその不確かさ
+// 不確かさは区間の半幅で, 標準不確かさは一様分布とみなして求める
+#[derive(Debug, PartialEq)]
+pub struct Element {
+    pub symbol: &'static str,
+    pub atomic_number: u8,
+    atomic_weight: f64,
+    uncertainty: f64,
+}
+
+const fn element(symbol: &'static str, atomic_number: u8, atomic_weight: f64, uncertainty: f64) -> Element {
+    Element {
+        symbol,
+        atomic_number,
+        atomic_weight,
+        uncertainty,
+    }
+}
+
+pub const ELEMENTS: [Element; 83] = [
+    element("H", 1, 1.0080, 0.0002),
+    element("He", 2, 4.0026, 0.0001),
+    element("Li", 3, 6.94, 0.06),
+    element("Be", 4, 9.0122, 0.0001),
+    element("B", 5, 10.81, 0.02),
+    element("C", 6, 12.011, 0.002),
+    element("N", 7, 14.007, 0.001),
+    element("O", 8, 15.999, 0.001),
+    element("F", 9, 18.998, 0.001),
+    element("Ne", 10, 20.180, 0.001),
+    element("Na", 11, 22.990, 0.001),
+    element("Mg", 12, 24.305, 0.002),
+    element("Al", 13, 26.982, 0.001),
+    element("Si", 14, 28.085, 0.001),
+    element("P", 15, 30.974, 0.001),
+    element("S", 16, 32.06, 0.02),
+    element("Cl", 17, 35.45, 0.01),
+    element("Ar", 18, 39.95, 0.16),
+    element("K", 19, 39.098, 0.001),
+    element("Ca", 20, 40.078, 0.004),
+    element("Sc", 21, 44.956, 0.001),
+    element("Ti", 22, 47.867, 0.001),
+    element("V", 23, 50.942, 0.001),
+    element("Cr", 24, 51.996, 0.001),
+    element("Mn", 25, 54.938, 0.001),
+    element("Fe", 26, 55.845, 0.002),
+    element("Co", 27, 58.933, 0.001),
+    element("Ni", 28, 58.693, 0.001),
+    element("Cu", 29, 63.546, 0.003),
+    element("Zn", 30, 65.38, 0.02),
+    element("Ga", 31, 69.723, 0.001),
+    element("Ge", 32, 72.630, 0.008),
+    element("As", 33, 74.922, 0.001),
+    element("Se", 34, 78.971, 0.008),
+    element("Br", 35, 79.904, 0.003),
+    element("Kr", 36, 83.798, 0.002),
+    element("Rb", 37, 85.468, 0.001),
+    element("Sr", 38, 87.62, 0.01),
+    element("Y", 39, 88.906, 0.001),
+    element("Zr", 40, 91.224, 0.002),
+    element("Nb", 41, 92.906, 0.001),
+    element("Mo", 42, 95.95, 0.01),
+    element("Ru", 44, 101.07, 0.02),
+    element("Rh", 45, 102.91, 0.01),
+    element("Pd", 46, 106.42, 0.01),
+    element("Ag", 47, 107.87, 0.01),
+    element("Cd", 48, 112.41, 0.01),
+    element("In", 49, 114.82, 0.01),
+    element("Sn", 50, 118.71, 0.01),
+    element("Sb", 51, 121.76, 0.01),
+    element("Te", 52, 127.60, 0.03),
+    element("I", 53, 126.90, 0.01),
+    element("Xe", 54, 131.29, 0.01),
+    element("Cs", 55, 132.91, 0.01),
+    element("Ba", 56, 137.33, 0.01),
+    element("La", 57, 138.91, 0.01),
+    element("Ce", 58, 140.12, 0.01),
+    element("Pr", 59, 140.91, 0.01),
+    element("Nd", 60, 144.24, 0.01),
+    element("Sm", 62, 150.36, 0.02),
+    element("Eu", 63, 151.96, 0.01),
+    element("Gd", 64, 157.25, 0.03),
+    element("Tb", 65, 158.93, 0.01),
+    element("Dy", 66, 162.50, 0.01),
+    element("Ho", 67, 164.93, 0.01),
+    element("Er", 68, 167.26, 0.01),
+    element("Tm", 69, 168.93, 0.01),
+    element("Yb", 70, 173.05, 0.02),
+    element("Lu", 71, 174.97, 0.01),
+    element("Hf", 72, 178.49, 0.01),
+    element("Ta", 73, 180.95, 0.01),
+    element("W", 74, 183.84, 0.01),
+    element("Re", 75, 186.21, 0.01),
+    element("Os", 76, 190.23, 0.03),
+    element("Ir", 77, 192.22, 0.01),
+    element("Pt", 78, 195.08, 0.02),
+    element("Au", 79, 196.97, 0.01),
+    element("Hg", 80, 200.59, 0.01),
+    element("Tl", 81, 204.38, 0.01),
+    element("Pb", 82, 207.2, 1.1),
+    element("Bi", 83, 208.98, 0.01),
+    element("Th", 90, 232.04, 0.01),
+    element("U", 92, 238.03, 0.01),
+];
+
+// 同位体の質量 (g/mol), 不確かさは原子量に比べて無視できるので固定値
+const ISOTOPES: [(&str, u16, f64); 18] = [
+    ("H", 1, 1.007825),
+    ("H", 2, 2.014102),
+    ("H", 3, 3.016049),
+    ("C", 12, 12.0),
+    ("C", 13, 13.003355),
+    ("C", 14, 14.003242),
+    ("N", 14, 14.003074),
+    ("N", 15, 15.000109),
+    ("O", 16, 15.994915),
+    ("O", 17, 16.999132),
+    ("O", 18, 17.999160),
+    ("S", 32, 31.972071),
+    ("S", 34, 33.967867),
+    ("Cl", 35, 34.968853),
+    ("Cl", 37, 36.965903),
+    ("Fe", 54, 53.939609),
+    ("Fe", 56, 55.934936),
+    ("Fe", 57, 56.935393),
+];
+const ISOTOPE_UNCERTAINTY: f64 = 1e-6;
+
+pub fn find_element(symbol: &str) -> Option<&'static Element> {
+    ELEMENTS.iter().find(|e| e.symbol == symbol)
+}
+
+pub fn find_element_by_number(atomic_number: u8) -> Option<&'static Element> {
+    ELEMENTS.iter().find(|e| e.atomic_number == atomic_number)
+}
+
+impl Element {
+    pub fn get_atomic_weight(&self) -> MolarMass {
+        DimSigDig::molar_mass_from(self.atomic_weight)
+            .set_uncertainty(self.get_standard_uncertainty())
+            .fit_sig_dig_to_uncertainty()
+    }
+    pub(crate) fn get_raw_atomic_weight(&self) -> f64 {
+        self.atomic_weight
+    }
+    pub(crate) fn get_standard_uncertainty(&self) -> f64 {
+        self.uncertainty / 3_f64.sqrt()
+    }
+    // 同位体の質量とその標準不確かさ
+    pub(crate) fn get_isotope_mass(&self, mass_number: u16) -> Option<(f64, f64)> {
+        ISOTOPES
+            .iter()
+            .find(|(symbol, a, _)| *symbol == self.symbol && *a == mass_number)
+            .map(|(_, _, mass)| (*mass, ISOTOPE_UNCERTAINTY))
+    }
+}
@@ -9,10 +9,68 @@ use apparatus::*;
 mod dim_sig_dig;
 use dim_sig_dig::*;
 
+mod formula;
+use formula::*;
+
+mod catalog;
+use catalog::*;
+
+mod calibration;
+
+mod photometry;
+use photometry::*;
+
+mod temperature;
+use temperature::*;
+
+mod titration;
+use titration::*;
+
+mod acid_base;
+use acid_base::*;
+
+mod activity;
+use activity::*;
+
+mod solubility;
+
+mod reaction;
+use reaction::*;
+
+mod redox;
+use redox::*;
+
+mod buffer;
+use buffer::*;
+
+mod monte_carlo;
+use monte_carlo::*;
+
+mod planner;
+use planner::*;
+
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    fn mohr() -> Substance {
+        Substance::create("Mohr".to_string(), "392.1 g/mol".parse().unwrap())
+    }
+
+    // モール塩 0.4019 g を 20 mL に溶かして 100 mL にし, 5 mL を 200 mL に薄める
+    fn mohr_standard(mohr: Substance, sampler: &Sampler) -> VolumetricFlask<200> {
+        Beaker::<100>::new()
+            .set_sampler(sampler.clone())
+            .add_substance(Solid::create(mohr, "0.4019 g".parse().unwrap(), 0.0.into()))
+            .fillup_to("20 mL".parse().unwrap())
+            .into_volumetric_flask(VolumetricFlask::<100>::new())
+            .fillup()
+            .into_pipette(Pipette::<5>::new())
+            .into_flask(VolumetricFlask::<200>::new())
+            .fillup()
+    }
+
     #[test]
     fn mohr1() {
         let mut beaker = Beaker::<100>::new()
@@ -68,6 +126,948 @@ mod tests {
             });
     }
 
+    #[test]
+    fn mohr_parsed() {
+        let mohr_concentration = mohr_standard(mohr(), &Sampler::default())
+            .get_concentration_by_name("Mohr");
+        let expected: Concentration = "2.562e-4 mol/L".parse().unwrap();
+        println!("{} is close to {}", mohr_concentration, expected);
+        assert!(mohr_concentration.is_close_to(&expected));
+        assert!("20.0 g".parse::<Volume>().is_err());
+    }
+
+    #[test]
+    fn mohr_from_formula() {
+        let mohr = Substance::from_formula("Mohr".to_string(), "(NH4)2Fe(SO4)2·6H2O").unwrap();
+        let c = mohr_standard(mohr, &Sampler::default()).get_concentration_by_name("Mohr");
+        let expected: Concentration = "2.562e-4 mol/L".parse().unwrap();
+        assert!(c.is_close_to(&expected));
+
+        let overflow = Substance::from_formula("Mohr".to_string(), "(NH4)2Fe(SO4)4294967295").unwrap_err();
+        assert!(matches!(overflow, FormulaError::InvalidNumber(_)));
+    }
+
+    #[test]
+    fn mohr_element() {
+        let mohr = Substance::from_formula("Mohr".to_string(), "(NH4)2Fe(SO4)2·6H2O").unwrap();
+        let flask = Beaker::<100>::new()
+            .add_substance(Solid::create(mohr, "0.4019 g".parse().unwrap(), 0.0.into()))
+            .fillup_to("20 mL".parse().unwrap())
+            .into_volumetric_flask(VolumetricFlask::<100>::new())
+            .fillup();
+        let fe = flask.get_concentration_of_element("Fe");
+        let expected: Concentration = "1.025e-2 mol/L".parse().unwrap();
+        println!("{} is close to {}", fe, expected);
+        assert!(fe.is_close_to(&expected));
+        // N は Fe の2倍
+        let n = flask.get_mol_of_element("N");
+        assert!(n.is_close_to(&(flask.get_mol_of_element("Fe") * DimSigDig::nodim_from(2.0))));
+        // 0.4019 g × 55.845 / 392.13 / 0.100 L = 572.4 mg/L
+        let fe_ppm = flask.get_mass_concentration_of_element("Fe");
+        let expected_ppm = DimSigDig::milli_gram_per_liter_from(572.4).set_sig_dig(4);
+        println!("{} is close to {}", fe_ppm, expected_ppm);
+        assert!(fe_ppm.is_close_to(&expected_ppm));
+
+        // 組成式の無い溶質が混ざっていても数えないだけ
+        let unknown = Substance::create("Unknown".to_string(), "100.0 g/mol".parse().unwrap());
+        let mixed = Beaker::<100>::new()
+            .add_substance(Solid::create(unknown, "0.1000 g".parse().unwrap(), 0.0.into()))
+            .add_solution(flask.get_solution().clone());
+        assert!(mixed.get_solution().get_mol_of_element("Fe").is_close_to(&flask.get_mol_of_element("Fe")));
+        assert_eq!(mixed.get_solution().get_mol_of_element("Cl").get_raw_num(), 0.0);
+    }
+
+    #[test]
+    fn mohr_uncertainty() {
+        let mohr_concentration = mohr_standard(mohr(), &Sampler::default())
+            .get_concentration_by_name("Mohr")
+            .convert_to_molar()
+            .normalized();
+        println!("{}", mohr_concentration.format_with_uncertainty());
+        // ピペットの許容誤差が支配的 (0.03 / √3 / 5 ≈ 0.35 %)
+        let r = mohr_concentration.get_relative_uncertainty();
+        assert!(0.0035 < r && r < 0.0040);
+        assert_eq!(
+            mohr_concentration.format_with_uncertainty(),
+            "2.562(9)×10⁻⁴ [mol dm^-3]"
+        );
+    }
+
+    #[test]
+    fn mohr_monte_carlo() {
+        use rand::{rngs::StdRng, SeedableRng};
+        let procedure = |sampler: &Sampler| mohr_standard(mohr(), sampler).get_concentration();
+        let result = MonteCarlo::new(StdRng::seed_from_u64(1), 2000).run(procedure);
+        let mohr = &result["Mohr"];
+        let expected: Concentration = "2.562e-4 mol/L".parse().unwrap();
+        println!("{}", mohr.mean().format_with_uncertainty());
+        assert!((mohr.mean().get_raw_num() - 2.5625e-4).abs() < 0.002e-4);
+        // 解析的な伝播 (約0.37 %) と同程度になる
+        let r = mohr.mean().get_relative_uncertainty();
+        assert!(0.0030 < r && r < 0.0045);
+        let (low, high) = mohr.coverage_interval(0.95);
+        assert!(low < expected && expected < high);
+
+        let again = MonteCarlo::new(StdRng::seed_from_u64(1), 2000).run(procedure);
+        assert_eq!(mohr.get_samples(), again["Mohr"].get_samples());
+        // 試行の外では値はばらつかない
+        assert!(procedure(&Sampler::default())["Mohr"].is_close_to(&expected));
+    }
+
+    #[test]
+    fn purity() {
+        let khp = Substance::from_formula("KHP".to_string(), "KHC8H4O4").unwrap();
+        let mass: Mass = "0.5106 g".parse().unwrap();
+        let pure = Solid::create(khp.clone(), mass, 0.0.into());
+        let assayed = Solid::create(
+            khp.set_assay_range("98.0 %".parse().unwrap(), "102.0 %".parse().unwrap()),
+            mass,
+            0.0.into(),
+        );
+        let mut solution = Solution::new();
+        solution.add_substance(pure);
+        let pure_mol = solution.get_mol_by_name("KHP");
+        let mut solution = Solution::new();
+        solution.add_substance(assayed.clone());
+        let assayed_mol = solution.get_mol_by_name("KHP");
+        // 中央値は100 %なのでmol数は同じで, 不確かさだけ大きくなる
+        assert!(pure_mol.is_close_to(&assayed_mol));
+        assert!(assayed_mol.get_relative_uncertainty() > 0.01);
+
+        let khp_fraction = assayed.get_substance().get_mass_fraction("K");
+        let diluted = Substance::from_formula("KHP".to_string(), "KHC8H4O4")
+            .unwrap()
+            .set_purity("99.5 %".parse().unwrap());
+        let mut solution = Solution::new();
+        solution.add_substance(Solid::create(diluted, mass, 0.0.into()));
+        let expected = pure_mol * DimSigDig::nodim_from(0.995);
+        assert!(solution.get_mol_by_name("KHP").is_close_to(&expected));
+        // 元素の質量もmol数と同じだけ減る
+        let k_mass = solution.get_mass_of_element("K");
+        let k_mol = solution.get_mol_of_element("K");
+        let k = Substance::from_formula("K".to_string(), "K").unwrap().get_molar_mass();
+        assert!(k_mass.is_close_to(&(k_mol * k)));
+        assert!(k_mass.is_close_to(&(mass * DimSigDig::nodim_from(0.995) * khp_fraction)));
+    }
+
+    #[test]
+    fn stock_reagent() {
+        let hcl = StockReagent::create(
+            Substance::from_formula("HCl".to_string(), "HCl").unwrap(),
+            "1.19 g/mL".parse().unwrap(),
+            "37.0 %".parse().unwrap(),
+        );
+        // 1.19 × 0.370 / 36.46 × 1000 = 12.08 mol/L
+        assert!(hcl.get_concentration().is_close_to(&DimSigDig::molar_from(12.08).set_sig_dig(3)));
+
+        let flask = Pipette::<5>::new()
+            .aspirate_reagent(&hcl)
+            .into_flask(VolumetricFlask::<100>::new())
+            .fillup();
+        let expected = DimSigDig::molar_from(0.604).set_sig_dig(3);
+        assert!(flask.get_concentration_by_name("HCl").convert_to_molar().is_close_to(&expected));
+
+        let mut beaker = MeasuringCylinder::<10>::new()
+            .measure(&hcl, "8.3 mL".parse().unwrap())
+            .into_beaker(Beaker::<100>::new())
+            .fillup_to("50 mL".parse().unwrap());
+        let flask = beaker.into_volumetric_flask(VolumetricFlask::<100>::new()).fillup();
+        let expected = DimSigDig::molar_from(1.00).set_sig_dig(2);
+        assert!(flask.get_concentration_by_name("HCl").convert_to_molar().is_close_to(&expected));
+    }
+
+    #[test]
+    fn round_mass() {
+        let planner = Planner::new();
+        for (grams, expected) in [(0.98034, "0.9803"), (12.34567, "12.3457"), (9.99996, "10.0000"), (0.00001, "0.0000")] {
+            let mass = planner.round_mass(grams);
+            assert_eq!(format!("{}", mass.get_digit()), expected);
+            assert_eq!(mass, format!("{} g", expected).parse().unwrap());
+        }
+    }
+
+    #[test]
+    fn plan_preparation() {
+        let mohr = Substance::from_formula("Mohr".to_string(), "(NH4)2Fe(SO4)2·6H2O").unwrap();
+        let target: Concentration = "0.0100 mol/L".parse().unwrap();
+        let plan = Planner::new()
+            .plan(&mohr, target, "250 mL".parse().unwrap())
+            .unwrap();
+        println!("{}", plan);
+        // 0.0100 × 0.250 × 392.13 = 0.9803 g
+        match &plan.get_steps()[0] {
+            Step::Weigh { mass, .. } => assert!(mass.is_close_to(&"0.9803 g".parse().unwrap())),
+            step => panic!("unexpected first step: {}", step),
+        }
+        assert!(matches!(plan.get_steps()[2], Step::TransferToFlask { flask: 250 }));
+        assert!(plan.get_expected_concentration().is_close_to(&target));
+
+        // はかりとれないほど薄いときは希釈する
+        let target: Concentration = "1.000e-5 mol/L".parse().unwrap();
+        let plan = Planner::new()
+            .plan(&mohr, target, "100 mL".parse().unwrap())
+            .unwrap();
+        println!("{}", plan);
+        assert!(plan.get_steps().iter().any(|s| matches!(s, Step::Pipette { .. })));
+        assert!(plan.get_expected_concentration().is_close_to(&target));
+    }
+
+    #[test]
+    fn plan_dilution() {
+        let mohr = Substance::from_formula("Mohr".to_string(), "(NH4)2Fe(SO4)2·6H2O").unwrap();
+        let stock = StockReagent::from_concentration(mohr, "1.000e-2 mol/L".parse().unwrap());
+        let target: Concentration = "1.000e-4 mol/L".parse().unwrap();
+
+        // 100倍なら一段で足りる
+        let plan = DilutionPlanner::default().plan(&stock, target).unwrap();
+        println!("{}", plan);
+        assert_eq!(plan.get_steps().len(), 3);
+        assert!(plan.get_expected_concentration().is_close_to(&target));
+
+        // 大きいフラスコがなければ二段 (20倍と5倍)
+        let plan = DilutionPlanner::new(&[50, 100], &[5, 10])
+            .plan(&stock, target)
+            .unwrap();
+        println!("{}", plan);
+        assert!(matches!(
+            plan.get_steps()[1],
+            Step::Pipette { pipette: 5, flask: 100, .. }
+        ));
+        assert_eq!(plan.get_steps().len(), 5);
+        assert!(plan.get_expected_concentration().is_close_to(&target));
+
+        let target = target * DimSigDig::nodim_from(3.0);
+        assert!(DilutionPlanner::new(&[100], &[10]).plan(&stock, target).is_none());
+
+        // 10 mLのメスフラスコから20 mLはとれないので, 1 mL → 10 mL を三回
+        let stock = StockReagent::from_concentration(stock.get_substance().clone(), "0.1000 mol/L".parse().unwrap());
+        let target: Concentration = "1.000e-4 mol/L".parse().unwrap();
+        let plan = DilutionPlanner::new(&[10, 200], &[1, 20])
+            .plan(&stock, target)
+            .unwrap();
+        println!("{}", plan);
+        let stages: Vec<_> = plan
+            .get_steps()
+            .iter()
+            .filter_map(|s| match s {
+                Step::Pipette { pipette, flask, .. } => Some((*pipette, *flask)),
+                _ => None,
+            })
+            .collect();
+        assert!(stages.windows(2).all(|w| w[1].0 < w[0].1));
+        assert!(plan.get_expected_concentration().is_close_to(&target));
+    }
+
+    #[test]
+    fn standard_series() {
+        let mut stock = mohr_standard(mohr(), &Sampler::default());
+        let c = stock.get_concentration_by_name("Mohr");
+
+        // mohr1と同じ 5 mL × 0~4回 を50 mLに
+        let standards = StandardSeries::linear(0.0.into(), c * DimSigDig::nodim_from(0.4), 5)
+            .set_flasks(&[50, 100])
+            .set_pipettes(&[5])
+            .prepare("Mohr", &mut stock)
+            .unwrap();
+        assert_eq!(standards.len(), 5);
+        for (i, standard) in standards.iter().enumerate() {
+            println!("{} mL × {} -> {}", standard.get_pipette(), standard.get_times(), standard.get_concentration());
+            assert_eq!(standard.get_flask(), 50);
+            assert_eq!(standard.get_times(), i);
+            assert!(standard.get_concentration().is_close_to(&(c * DimSigDig::nodim_from(i as f64 / 10.0))));
+        }
+        assert!(stock.get_solution().get_volume().is_close_to(&"150 mL".parse().unwrap()));
+
+        // 測る前に吸光度の見当をつける (空試験は低すぎ, 4回分は高すぎ)
+        let fe_phen = BeerLambert::new(
+            MolarAbsorptivity::liter_per_mol_per_centi_meter_from(1.11e4).set_sig_dig(3),
+        );
+        let readings = fe_phen.predict_readings(&standards);
+        assert!(matches!(readings[0].1, Some(RangeWarning::TooLow(_))));
+        assert!(readings[1..4].iter().all(|(_, warning)| warning.is_none()));
+        assert!(matches!(readings[4].1, Some(RangeWarning::TooHigh(_))));
+
+        let log = StandardSeries::logarithmic("1e-6 M".parse().unwrap(), "1e-4 M".parse().unwrap(), 3);
+        assert!(log.get_targets()[1].is_close_to(&DimSigDig::molar_from(1e-5)));
+    }
+
+    #[test]
+    fn permanganate_titration() {
+        // 0.0100 mol/L 過マンガン酸カリウムを250 mL
+        let kmno4 = Substance::from_formula("KMnO4".to_string(), "KMnO4").unwrap();
+        let mut titrant = Beaker::<200>::new()
+            .add_substance(Solid::create(kmno4, "0.3951 g".parse().unwrap(), 0.0.into()))
+            .fillup_to("100 mL".parse().unwrap())
+            .into_volumetric_flask(VolumetricFlask::<250>::new())
+            .fillup();
+        let mut burette = Burette::<50>::new(BuretteClass::A)
+            .fill_from(&mut titrant)
+            .read_initial("0.05 mL".parse().unwrap());
+
+        // 約0.05 mol/L のモール塩溶液を20 mLとる
+        let mohr = Substance::from_formula("Mohr".to_string(), "(NH4)2Fe(SO4)2·6H2O").unwrap();
+        let mut analyte = Beaker::<100>::new()
+            .add_substance(Solid::create(mohr, "1.9607 g".parse().unwrap(), 0.0.into()))
+            .fillup_to("50 mL".parse().unwrap())
+            .into_volumetric_flask(VolumetricFlask::<100>::new())
+            .fillup();
+        let beaker = analyte
+            .into_pipette(Pipette::<20>::new())
+            .into_beaker(Beaker::<100>::new());
+
+        let titration = Titration::new(
+            titrant.get_solution(),
+            beaker.get_solution(),
+            Stoichiometry::new("KMnO4", 1, "Mohr", 5),
+        );
+        let equivalence = titration.get_equivalence_volume();
+        println!("equivalence: {}", equivalence);
+        assert!(equivalence.is_close_to(&"20.0 mL".parse().unwrap()));
+
+        let final_reading: Volume = "20.05 mL".parse().unwrap();
+        let delivered = burette.get_delivered_volume(final_reading);
+        // 読み2回 (0.005/√3) とA級の許容誤差 (0.05/√3)
+        assert!((delivered.get_uncertainty().get_raw_num() - 0.0292).abs() < 0.0005);
+        let beaker = burette.into_beaker(final_reading, beaker);
+        assert_eq!(burette.get_readings().len(), 1);
+        assert!(beaker.get_solution().get_volume().is_close_to(&"40.0 mL".parse().unwrap()));
+
+        let c = titration.get_analyte_concentration(delivered);
+        println!("Mohr: {}", c.format_with_uncertainty());
+        assert!(c.is_close_to(&analyte.get_concentration_by_name("Mohr")));
+    }
+
+    fn filled_burette(class: BuretteClass) -> Burette<10> {
+        let nacl = Substance::from_formula("NaCl".to_string(), "NaCl").unwrap();
+        let mut flask = Beaker::<100>::new()
+            .add_substance(Solid::create(nacl, "0.5844 g".parse().unwrap(), 0.0.into()))
+            .fillup_to("50 mL".parse().unwrap())
+            .into_volumetric_flask(VolumetricFlask::<100>::new())
+            .fillup();
+        Burette::<10>::new(class)
+            .fill_from(&mut flask)
+            .read_initial("0.00 mL".parse().unwrap())
+    }
+
+    #[test]
+    fn burette_class() {
+        let a = filled_burette(BuretteClass::A).get_delivered_volume("5.00 mL".parse().unwrap());
+        let b = filled_burette(BuretteClass::B).get_delivered_volume("5.00 mL".parse().unwrap());
+        // B級は許容誤差がA級の2倍
+        assert!(b.get_uncertainty().get_raw_num() > a.get_uncertainty().get_raw_num() * 1.9);
+    }
+
+    #[test]
+    #[should_panic(expected = "beyond the scale")]
+    fn burette_beyond_scale() {
+        filled_burette(BuretteClass::A).get_delivered_volume("10.50 mL".parse().unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "does not have")]
+    fn burette_over_remaining() {
+        let mut burette = filled_burette(BuretteClass::A);
+        let beaker = burette.into_beaker("8.00 mL".parse().unwrap(), Beaker::<100>::new());
+        // 読みをやり直しても残りより多くは出せない
+        let mut burette = burette.read_initial("0.00 mL".parse().unwrap());
+        burette.into_beaker("5.00 mL".parse().unwrap(), beaker);
+    }
+
+    #[test]
+    fn acid_base_titration_curve() {
+        let naoh = Substance::from_formula("NaOH".to_string(), "NaOH")
+            .unwrap()
+            .set_acid_base(AcidBase::strong_base(1));
+        let titrant = Beaker::<200>::new()
+            .add_substance(Solid::create(naoh, "1.000 g".parse().unwrap(), 0.0.into()))
+            .fillup_to("100 mL".parse().unwrap())
+            .into_volumetric_flask(VolumetricFlask::<250>::new())
+            .fillup();
+
+        // 0.1000 mol/L 酢酸 25 mL
+        let acetic_acid = Substance::from_formula("AcOH".to_string(), "CH3COOH")
+            .unwrap()
+            .set_acid_base(AcidBase::weak_acid(&[4.76]));
+        let mut flask = Beaker::<200>::new()
+            .add_substance(Solid::create(acetic_acid, "1.501 g".parse().unwrap(), 0.0.into()))
+            .fillup_to("100 mL".parse().unwrap())
+            .into_volumetric_flask(VolumetricFlask::<250>::new())
+            .fillup();
+        let analyte = flask
+            .into_pipette(Pipette::<25>::new())
+            .into_beaker(Beaker::<100>::new());
+
+        let curve = TitrationCurve::simulate(
+            titrant.get_solution(),
+            analyte.get_solution(),
+            "40 mL".parse().unwrap(),
+            "0.05 mL".parse().unwrap(),
+        );
+        let points = curve.get_points();
+        assert!((points[0].1 - 2.88).abs() < 0.01);
+        // 半当量点ではpH = pKa
+        assert!((points[250].1 - 4.76).abs() < 0.01);
+        let equivalence = curve.get_equivalence_volumes();
+        assert_eq!(equivalence.len(), 1);
+        assert!((equivalence[0].get_raw_num() - 25.0).abs() < 0.05);
+        let indicator = curve.select_indicator(equivalence[0]).unwrap();
+        assert_eq!(indicator.get_name(), "phenolphthalein");
+
+        // リン酸は2段目まで見える
+        let phosphoric_acid = Substance::from_formula("H3PO4".to_string(), "H3PO4")
+            .unwrap()
+            .set_acid_base(AcidBase::weak_acid(&[2.15, 7.20, 12.35]));
+        let analyte = Beaker::<100>::new()
+            .add_substance(Solid::create(phosphoric_acid, "0.1225 g".parse().unwrap(), 0.0.into()))
+            .fillup_to("25.0 mL".parse().unwrap());
+        let curve = TitrationCurve::simulate(
+            titrant.get_solution(),
+            analyte.get_solution(),
+            "40 mL".parse().unwrap(),
+            "0.05 mL".parse().unwrap(),
+        );
+        let equivalence = curve.get_equivalence_volumes();
+        assert_eq!(equivalence.len(), 2);
+        assert!((equivalence[0].get_raw_num() - 12.5).abs() < 0.1);
+        assert!((equivalence[1].get_raw_num() - 25.0).abs() < 0.1);
+
+        // 塩酸で水酸化ナトリウムを滴定するとpHは下がっていく
+        let hcl = Substance::from_formula("HCl".to_string(), "HCl")
+            .unwrap()
+            .set_acid_base(AcidBase::strong_acid(1));
+        let titrant = Beaker::<200>::new()
+            .add_substance(Solid::create(hcl, "0.3646 g".parse().unwrap(), 0.0.into()))
+            .fillup_to("100 mL".parse().unwrap());
+        let naoh = Substance::from_formula("NaOH".to_string(), "NaOH")
+            .unwrap()
+            .set_acid_base(AcidBase::strong_base(1));
+        let analyte = Beaker::<100>::new()
+            .add_substance(Solid::create(naoh, "0.1000 g".parse().unwrap(), 0.0.into()))
+            .fillup_to("25.0 mL".parse().unwrap());
+        let curve = TitrationCurve::simulate(
+            titrant.get_solution(),
+            analyte.get_solution(),
+            "40 mL".parse().unwrap(),
+            "0.05 mL".parse().unwrap(),
+        );
+        let points = curve.get_points();
+        assert!(points[0].1 > points[points.len() - 1].1);
+        let equivalence = curve.get_equivalence_volumes();
+        assert_eq!(equivalence.len(), 1);
+        assert!((equivalence[0].get_raw_num() - 25.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn ph_and_speciation() {
+        let acetic_acid = Substance::from_formula("AcOH".to_string(), "CH3COOH")
+            .unwrap()
+            .set_acid_base(AcidBase::weak_acid(&[4.76]));
+        let sodium_acetate = Substance::from_formula("AcONa".to_string(), "CH3COONa")
+            .unwrap()
+            .set_acid_base(AcidBase::weak_acid(&[4.76]).set_counter_charge(1));
+        // 0.100 mol/L ずつの酢酸緩衝液
+        let buffer = Beaker::<200>::new()
+            .add_substance(Solid::create(acetic_acid, "0.601 g".parse().unwrap(), 0.0.into()))
+            .add_substance(Solid::create(sodium_acetate, "0.820 g".parse().unwrap(), 0.0.into()))
+            .fillup_to("100 mL".parse().unwrap());
+        let ph = buffer.get_solution().get_ph();
+        println!("acetate buffer: pH {}", ph.get_digit());
+        assert_eq!(format!("{}", ph.get_digit()), "4.760");
+        let species = Equilibrium::of(buffer.get_solution()).get_species("AcOH").unwrap();
+        assert!((species[0].get_molar_num() / species[1].get_molar_num() - 1.0).abs() < 0.01);
+
+        // 塩化アンモニウム 0.100 mol/L (pKb 4.75)
+        let ammonium_chloride = Substance::from_formula("NH4Cl".to_string(), "NH4Cl")
+            .unwrap()
+            .set_acid_base(AcidBase::weak_base_from_pkb(&[4.75]).set_counter_charge(-1));
+        let solution = Beaker::<200>::new()
+            .add_substance(Solid::create(ammonium_chloride, "0.535 g".parse().unwrap(), 0.0.into()))
+            .fillup_to("100 mL".parse().unwrap());
+        let ph = solution.get_solution().get_ph();
+        println!("NH4Cl: pH {}", ph.get_digit());
+        assert!((ph.get_raw_num() - 5.13).abs() < 0.01);
+
+        // 炭酸ナトリウム 0.0500 mol/L はほとんどHCO3⁻とCO3²⁻
+        let sodium_carbonate = Substance::from_formula("Na2CO3".to_string(), "Na2CO3")
+            .unwrap()
+            .set_acid_base(AcidBase::weak_acid(&[6.35, 10.33]).set_counter_charge(2));
+        let solution = Beaker::<200>::new()
+            .add_substance(Solid::create(sodium_carbonate, "0.530 g".parse().unwrap(), 0.0.into()))
+            .fillup_to("100 mL".parse().unwrap());
+        let equilibrium = Equilibrium::of(solution.get_solution());
+        println!("Na2CO3: pH {}", equilibrium.get_ph().get_digit());
+        assert!((equilibrium.get_ph().get_raw_num() - 11.50).abs() < 0.01);
+        let species = equilibrium.get_species("Na2CO3").unwrap();
+        assert!(species[0].get_molar_num() < 1e-6);
+        assert!(equilibrium.get_oh_concentration() > equilibrium.get_h_concentration());
+    }
+
+    #[test]
+    fn buffer_recipe() {
+        // 氷酢酸 (1.049 g/mL, 99.8 %) と酢酸ナトリウム三水和物で pH 5.00, 0.100 mol/L を250 mL
+        let acetic_acid = Substance::from_formula("AcOH".to_string(), "CH3COOH")
+            .unwrap()
+            .set_acid_base(AcidBase::weak_acid(&[4.76]));
+        let glacial = StockReagent::create(
+            acetic_acid,
+            "1.049 g/mL".parse().unwrap(),
+            "99.8 %".parse().unwrap(),
+        );
+        let sodium_acetate = Substance::from_formula("AcONa".to_string(), "CH3COONa·3H2O")
+            .unwrap()
+            .set_acid_base(AcidBase::weak_acid(&[4.76]).set_counter_charge(1));
+        let recipe = BufferDesigner::new(
+            BufferComponent::Stock(glacial),
+            BufferComponent::Solid(sodium_acetate),
+        )
+        .design(DimSigDig::nodim_from(5.00), "0.100 mol/L".parse().unwrap(), "250 mL".parse().unwrap())
+        .unwrap();
+        println!("{}", recipe.get_plan());
+        println!("pH {}", recipe.get_expected_ph().get_digit());
+        assert!((recipe.get_expected_ph().get_raw_num() - 5.00).abs() < 0.01);
+        assert!(recipe.get_steps().iter().any(|s| matches!(s, Step::Measure { .. })));
+
+        // Tris-HCl pH 7.40 (37 ℃, I = 0.1 mol/L)
+        let tris = Substance::from_formula("Tris".to_string(), "C4H11NO3")
+            .unwrap()
+            .set_acid_base(AcidBase::weak_base(&[8.06]).set_temperature_coefficient(-0.028));
+        let hcl = Substance::from_formula("HCl".to_string(), "HCl")
+            .unwrap()
+            .set_acid_base(AcidBase::strong_acid(1));
+        let designer = BufferDesigner::new(
+            BufferComponent::Stock(StockReagent::from_concentration(hcl, "1.000 mol/L".parse().unwrap())),
+            BufferComponent::Solid(tris),
+        )
+        .set_temperature("37.0 °C".parse().unwrap())
+        .set_ionic_strength("0.1 mol/L".parse().unwrap());
+        let recipe = designer
+            .design(DimSigDig::nodim_from(7.40), "0.0500 mol/L".parse().unwrap(), "100 mL".parse().unwrap())
+            .unwrap();
+        println!("{}", recipe.get_plan());
+        assert!((recipe.get_expected_ph().get_raw_num() - 7.40).abs() < 0.01);
+        assert!(matches!(
+            designer.design(DimSigDig::nodim_from(4.0), "0.0500 mol/L".parse().unwrap(), "100 mL".parse().unwrap()),
+            Err(BufferError::OutOfRange { .. })
+        ));
+        // 酸・塩基の性質を持たない成分はエラー
+        let sodium_chloride = Substance::from_formula("NaCl".to_string(), "NaCl").unwrap();
+        let designer = BufferDesigner::new(
+            BufferComponent::Solid(sodium_chloride.clone()),
+            BufferComponent::Solid(sodium_chloride),
+        );
+        assert!(matches!(
+            designer.design(DimSigDig::nodim_from(7.0), "0.0500 mol/L".parse().unwrap(), "100 mL".parse().unwrap()),
+            Err(BufferError::NoBufferingSpecies)
+        ));
+    }
+
+    #[test]
+    fn ionic_strength_and_activity() {
+        let sodium = Ion::new("Na⁺", 1, 1, 450.0);
+        let sodium_chloride = Substance::from_formula("NaCl".to_string(), "NaCl")
+            .unwrap()
+            .set_ions(vec![sodium.clone(), Ion::new("Cl⁻", -1, 1, 300.0)]);
+        let sodium_sulfate = Substance::from_formula("Na2SO4".to_string(), "Na2SO4")
+            .unwrap()
+            .set_ions(vec![Ion::new("Na⁺", 1, 2, 450.0), Ion::new("SO4²⁻", -2, 1, 400.0)]);
+        // 0.00400 mol/L NaCl + 0.00200 mol/L Na2SO4 で I = 0.0100 mol/L
+        let solution = Beaker::<300>::new()
+            .add_substance(Solid::create(sodium_chloride, "0.0584 g".parse().unwrap(), 0.0.into()))
+            .add_substance(Solid::create(sodium_sulfate, "0.0710 g".parse().unwrap(), 0.0.into()))
+            .fillup_to("250 mL".parse().unwrap());
+        let solution = solution.get_solution();
+        let ionic_strength = solution.get_ionic_strength();
+        println!("I = {}", ionic_strength);
+        assert!((ionic_strength.get_molar_num() - 0.0100).abs() < 0.0001);
+        let ions = solution.get_ion_concentration();
+        assert!((ions["Na⁺"].get_molar_num() - 0.00800).abs() < 0.0001);
+
+        let activity = solution.get_activity(ActivityModel::DebyeHuckel);
+        println!("a(SO4²⁻) = {}", activity["SO4²⁻"].format_with_uncertainty());
+        let gamma = ActivityModel::DebyeHuckel.get_coefficient(&sodium, ionic_strength);
+        assert!(activity["Na⁺"].is_close_to(&activity_of(ions["Na⁺"], gamma)));
+        assert!((activity["SO4²⁻"].get_raw_num() / 0.00200 - 0.660).abs() < 0.01);
+        let davies = solution.get_activity(ActivityModel::Davies);
+        // I = 0.01 mol/L ではどちらの式もほぼ同じ
+        assert!((davies["SO4²⁻"].get_raw_num() / activity["SO4²⁻"].get_raw_num() - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn ionic_strength_without_ions() {
+        assert_eq!(Solution::new().get_ionic_strength().get_molar_num(), 0.0);
+        assert!(Solution::new().get_activity(ActivityModel::Davies).is_empty());
+        // 非電解質だけの溶液
+        let glucose = Substance::from_formula("glucose".to_string(), "C6H12O6").unwrap();
+        let solution = Beaker::<300>::new()
+            .add_substance(Solid::create(glucose, "1.802 g".parse().unwrap(), 0.0.into()))
+            .fillup_to("100 mL".parse().unwrap());
+        let solution = solution.get_solution();
+        assert_eq!(solution.get_ionic_strength().get_molar_num(), 0.0);
+        assert!(solution.get_activity(ActivityModel::DebyeHuckel).is_empty());
+    }
+
+    #[test]
+    fn precipitation_and_solubility() {
+        let barium_chloride = Substance::from_formula("BaCl2".to_string(), "BaCl2")
+            .unwrap()
+            .set_ions(vec![Ion::new("Ba²⁺", 2, 1, 500.0), Ion::new("Cl⁻", -1, 2, 300.0)]);
+        let sodium_sulfate = Substance::from_formula("Na2SO4".to_string(), "Na2SO4")
+            .unwrap()
+            .set_ions(vec![Ion::new("Na⁺", 1, 2, 450.0), Ion::new("SO4²⁻", -2, 1, 400.0)]);
+        let barium_sulfate = Substance::from_formula("BaSO4".to_string(), "BaSO4")
+            .unwrap()
+            .set_ions(vec![Ion::new("Ba²⁺", 2, 1, 500.0), Ion::new("SO4²⁻", -2, 1, 400.0)])
+            .set_ksp(1.1e-10);
+        let silver_chloride = Substance::from_formula("AgCl".to_string(), "AgCl")
+            .unwrap()
+            .set_ions(vec![Ion::new("Ag⁺", 1, 1, 250.0), Ion::new("Cl⁻", -1, 1, 300.0)])
+            .set_ksp(1.8e-10);
+        let salts = [barium_sulfate, silver_chloride, barium_chloride.clone()];
+
+        // 0.0100 mol/L ずつ50.0 mLを混ぜる
+        let mut solution = StockReagent::from_concentration(barium_chloride, "0.0100 mol/L".parse().unwrap())
+            .dispense("50.0 mL".parse().unwrap());
+        assert!(solution.check_precipitation(&salts).is_empty());
+        let sulfate = StockReagent::from_concentration(sodium_sulfate, "0.0100 mol/L".parse().unwrap())
+            .dispense("50.0 mL".parse().unwrap());
+        let precipitates = solution.mix(sulfate, &salts);
+        for p in precipitates.iter() {
+            println!("{}", p);
+        }
+        assert_eq!(precipitates.len(), 1);
+        assert_eq!(precipitates[0].get_substance().get_name(), "BaSO4");
+        assert!((precipitates[0].get_amount().get_raw_num() - 0.000499).abs() < 1e-6);
+        // 等量なので残るのは √Ksp
+        let barium = precipitates[0].get_dissolved()["Ba²⁺"].get_molar_num();
+        assert!((barium - 1.1e-10_f64.sqrt()).abs() < 1e-7);
+
+        // ホウ酸 (溶解度 47 g/L) 3 gを50 mLには溶かせない
+        let boric_acid = Substance::from_formula("H3BO3".to_string(), "H3BO3")
+            .unwrap()
+            .set_solubility("47 g/L".parse().unwrap());
+        let mut solution = Solution::new();
+        solution.add_substance(Solid::create(boric_acid.clone(), "3.09 g".parse().unwrap(), 0.0.into()));
+        solution.to_be("50 mL".parse().unwrap());
+        let undissolved = solution.check_solubility();
+        println!("{}", undissolved[0]);
+        assert_eq!(undissolved[0].get_name(), "H3BO3");
+        // ビーカーでも溶け残りは調べて知らせるだけ
+        let beaker = Beaker::<100>::new()
+            .add_substance(Solid::create(boric_acid.clone(), "3.09 g".parse().unwrap(), 0.0.into()))
+            .fillup_to("50 mL".parse().unwrap());
+        assert_eq!(beaker.get_solution().check_solubility().len(), 1);
+        // 水を増やした手順になる
+        let plan = Planner::new()
+            .plan(&boric_acid, "0.500 mol/L".parse().unwrap(), "100 mL".parse().unwrap())
+            .unwrap();
+        println!("{}", plan);
+        assert!(plan.get_steps().iter().any(|s| matches!(s, Step::Dissolve { beaker: 100, .. })));
+        plan.execute();
+    }
+
+    #[test]
+    fn aspirin_yield() {
+        let salicylic_acid = Substance::from_formula("salicylic acid".to_string(), "C7H6O3").unwrap();
+        let acetic_anhydride = Substance::from_formula("acetic anhydride".to_string(), "C4H6O3").unwrap();
+        let aspirin = Substance::from_formula("aspirin".to_string(), "C9H8O4").unwrap();
+        let acetic_acid = Substance::from_formula("acetic acid".to_string(), "C2H4O2").unwrap();
+        // どの化合物もH : O = 2 : 1 なので組成式だけでは係数が決まらない
+        assert_eq!(
+            Reaction::balance(
+                vec![salicylic_acid.clone(), acetic_anhydride.clone()],
+                vec![aspirin.clone(), acetic_acid.clone()],
+            )
+            .unwrap_err(),
+            ReactionError::Ambiguous
+        );
+        let reaction = Reaction::new(
+            vec![(salicylic_acid.clone(), 1), (acetic_anhydride.clone(), 1)],
+            vec![(aspirin, 1), (acetic_acid, 1)],
+        );
+        println!("{}", reaction);
+
+        let result = reaction
+            .run_with(&[
+                Solid::create(salicylic_acid.clone(), "2.000 g".parse().unwrap(), 0.0.into()),
+                Solid::create(acetic_anhydride.clone(), "5.00 g".parse().unwrap(), 0.0.into()),
+            ])
+            .unwrap();
+        assert_eq!(result.get_limiting_reagent(), "salicylic acid");
+        let theoretical = result.get_theoretical_mass("aspirin");
+        println!("theoretical: {}", theoretical.format_with_uncertainty());
+        assert_eq!(format!("{}", theoretical.get_digit()), "2.609");
+        let (name, left) = &result.get_excess()[0];
+        assert_eq!(name, "acetic anhydride");
+        assert!((left.get_raw_num() - 0.03450).abs() < 0.00001);
+        let percent = result.get_percent_yield("aspirin", "2.10 g".parse().unwrap());
+        println!("yield: {} %", percent.get_digit());
+        assert_eq!(format!("{}", percent.get_digit()), "80.5");
+
+        // 水の電気分解は溶媒しか反応物がないので限定試薬が決まらない
+        let substance = |formula: &str| Substance::from_formula(formula.to_string(), formula).unwrap();
+        let electrolysis = Reaction::balance(vec![substance("H2O")], vec![substance("H2"), substance("O2")]).unwrap();
+        assert_eq!(electrolysis.run_with(&[]).unwrap_err(), ReactionError::NoReactant);
+        let mut solution = Solution::new();
+        solution.to_be("10 mL".parse().unwrap());
+        assert_eq!(solution.react(&electrolysis, ReactionExtent::Complete).unwrap_err(), ReactionError::NoReactant);
+        let nan = Solid::create(salicylic_acid, DimSigDig::gram_from(f64::NAN), 0.0.into());
+        assert!(matches!(
+            reaction.run_with(&[nan, Solid::create(acetic_anhydride, "5.00 g".parse().unwrap(), 0.0.into())]),
+            Err(ReactionError::InvalidAmount(_))
+        ));
+    }
+
+    #[test]
+    fn react_in_solution() {
+        let hcl = Substance::from_formula("HCl".to_string(), "HCl")
+            .unwrap()
+            .set_acid_base(AcidBase::strong_acid(1));
+        let naoh = Substance::from_formula("NaOH".to_string(), "NaOH")
+            .unwrap()
+            .set_acid_base(AcidBase::strong_base(1));
+        let nacl = Substance::from_formula("NaCl".to_string(), "NaCl").unwrap();
+        let water = Substance::from_formula("H2O".to_string(), "H2O").unwrap();
+        let neutralization = Reaction::balance(vec![hcl.clone(), naoh.clone()], vec![nacl, water]).unwrap();
+
+        let mut solution = StockReagent::from_concentration(hcl, "0.1000 mol/L".parse().unwrap())
+            .dispense("25.00 mL".parse().unwrap());
+        let ph_before = solution.get_ph();
+        solution.add_solution(
+            StockReagent::from_concentration(naoh, "0.1000 mol/L".parse().unwrap())
+                .dispense("20.00 mL".parse().unwrap()),
+        );
+        let record = solution.react(&neutralization, ReactionExtent::Complete).unwrap();
+        println!("{}", record);
+        assert_eq!(format!("{}", record.get_extent().get_digit()), "0.002000");
+        assert_eq!(solution.get_mol_by_name("NaOH").get_raw_num(), 0.0);
+        let left = solution.get_mol_by_name("HCl").into_same_unit_with(&Mol::mol_from(0.0));
+        assert!((left.get_raw_num() - 0.000500).abs() < 1e-9);
+        assert!(record.get_change("H2O").is_none());
+        // 0.500 mmol / 45.00 mL
+        let ph = solution.get_ph();
+        println!("pH {} -> {}", ph_before.get_digit(), ph.get_digit());
+        assert!((ph.get_raw_num() - 1.954).abs() < 0.001);
+
+        // Fe³⁺ + SCN⁻ ⇌ FeSCN²⁺ (K = 138)
+        let iron = Substance::from_formula("Fe+3".to_string(), "Fe+3").unwrap();
+        let thiocyanate = Substance::from_formula("SCN-".to_string(), "SCN-").unwrap();
+        let complex = Substance::from_formula("FeSCN+2".to_string(), "FeSCN+2").unwrap();
+        let formation = Reaction::balance(vec![iron.clone(), thiocyanate.clone()], vec![complex]).unwrap();
+        let mut solution = StockReagent::from_concentration(iron, "0.00200 mol/L".parse().unwrap())
+            .dispense("50.0 mL".parse().unwrap());
+        solution.add_solution(
+            StockReagent::from_concentration(thiocyanate, "0.00200 mol/L".parse().unwrap())
+                .dispense("50.0 mL".parse().unwrap()),
+        );
+        let record = solution.react(&formation, ReactionExtent::Equilibrium(138.0)).unwrap();
+        println!("{}", record);
+        let c = solution.get_concentration();
+        let k = c["FeSCN+2"].get_molar_num() / (c["Fe+3"].get_molar_num() * c["SCN-"].get_molar_num());
+        assert!((k - 138.0).abs() < 0.01);
+        // 逆向きに戻すと同じ平衡に落ち着く
+        let again = solution.react(&formation, ReactionExtent::Equilibrium(138.0)).unwrap();
+        assert!(again.get_extent().get_raw_num().abs() < 1e-12);
+
+        // KMnO4, FeSO4, H2SO4 の溶液を混ぜて Fe²⁺ を酸化する. 溶質は塩なので反応式も塩で書く
+        let salt = |formula: &str, ions: Vec<Ion>| {
+            Substance::from_formula(formula.to_string(), formula).unwrap().set_ions(ions)
+        };
+        let permanganate = salt("KMnO4", vec![Ion::new("K+", 1, 1, 300.0), Ion::new("MnO4-", -1, 1, 350.0)]);
+        let iron_sulfate = salt("FeSO4", vec![Ion::new("Fe+2", 2, 1, 600.0), Ion::new("SO4-2", -2, 1, 400.0)]);
+        let sulfuric_acid = salt("H2SO4", vec![Ion::new("H+", 1, 2, 900.0), Ion::new("SO4-2", -2, 1, 400.0)]);
+        let substance = |formula: &str| Substance::from_formula(formula.to_string(), formula).unwrap();
+        let oxidation = Reaction::balance(
+            vec![permanganate.clone(), iron_sulfate.clone(), sulfuric_acid.clone()],
+            vec![substance("K2SO4"), substance("MnSO4"), substance("Fe2(SO4)3"), substance("H2O")],
+        )
+        .unwrap();
+        println!("{}", oxidation);
+        let mut solution = StockReagent::from_concentration(iron_sulfate, "0.1000 mol/L".parse().unwrap())
+            .dispense("25.00 mL".parse().unwrap());
+        solution.add_solution(
+            StockReagent::from_concentration(sulfuric_acid, "1.000 mol/L".parse().unwrap())
+                .dispense("10.00 mL".parse().unwrap()),
+        );
+        solution.add_solution(
+            StockReagent::from_concentration(permanganate, "0.02000 mol/L".parse().unwrap())
+                .dispense("10.00 mL".parse().unwrap()),
+        );
+        // イオンで書いた反応式は溶質に見つからない
+        let ionic = Reaction::new(
+            vec![(substance("MnO4-"), 1), (substance("H+"), 8), (substance("Fe+2"), 5)],
+            vec![(substance("Mn+2"), 1), (substance("Fe+3"), 5), (substance("H2O"), 4)],
+        );
+        assert_eq!(
+            solution.react(&ionic, ReactionExtent::Complete).unwrap_err(),
+            ReactionError::OnlyAsIon("MnO4-".to_string())
+        );
+        assert_eq!(ionic.run_in(&solution).unwrap_err(), ReactionError::OnlyAsIon("MnO4-".to_string()));
+        let record = solution.react(&oxidation, ReactionExtent::Complete).unwrap();
+        println!("{}", record);
+        assert_eq!(solution.get_mol_by_name("KMnO4").get_raw_num(), 0.0);
+        // 0.2000 mmol の MnO4⁻ で 1.000 mmol の Fe²⁺ が酸化される
+        let fe2 = solution.get_mol_by_name("FeSO4").into_same_unit_with(&Mol::milli_mol_from(0.0));
+        assert!((fe2.get_raw_num() - 1.500).abs() < 1e-9);
+        let fe3 = solution.get_mol_by_name("Fe2(SO4)3").into_same_unit_with(&Mol::milli_mol_from(0.0));
+        assert!((fe3.get_raw_num() - 0.5000).abs() < 1e-9);
+    }
+
+    #[test]
+    fn react_impure_reagent() {
+        let hcl = Substance::from_formula("HCl".to_string(), "HCl").unwrap();
+        let naoh = Substance::from_formula("NaOH".to_string(), "NaOH")
+            .unwrap()
+            .set_purity("98.0 %".parse().unwrap());
+        let nacl = Substance::from_formula("NaCl".to_string(), "NaCl").unwrap();
+        let water = Substance::from_formula("H2O".to_string(), "H2O").unwrap();
+        let neutralization =
+            Reaction::balance(vec![hcl.clone(), naoh.clone()], vec![nacl, water.clone()]).unwrap();
+
+        // 純度の違う同じ試薬は同じ物質としてまとまる
+        let mut solution = StockReagent::from_concentration(naoh.clone(), "0.1000 mol/L".parse().unwrap())
+            .dispense("10.00 mL".parse().unwrap());
+        let mut solid = Solution::new();
+        solid.add_substance(Solid::create(naoh, "0.04082 g".parse().unwrap(), 0.0.into()));
+        solution.add_solution(solid);
+        // 1.000 mmol + 0.04082 × 0.980 / 40.00 = 2.000 mmol
+        let n = solution.get_mol_by_name("NaOH").into_same_unit_with(&Mol::mol_from(0.0));
+        assert!((n.get_raw_num() - 0.002000).abs() < 1e-6);
+
+        solution.add_solution(
+            StockReagent::from_concentration(hcl, "0.1000 mol/L".parse().unwrap())
+                .dispense("15.00 mL".parse().unwrap()),
+        );
+        solution.react(&neutralization, ReactionExtent::Complete).unwrap();
+        let left = solution.get_mol_by_name("NaOH").into_same_unit_with(&Mol::mol_from(0.0));
+        assert!((left.get_raw_num() - 0.000500).abs() < 1e-6);
+
+        // 名前だけ同じ別の物質があれば中身を変えずにエラーにする
+        let typed = Substance::create("NaOH".to_string(), DimSigDig::molar_mass_from(40.0).set_sig_dig(3));
+        let other = Reaction::new(vec![(typed, 1)], vec![(water, 1)]);
+        assert_eq!(
+            solution.react(&other, ReactionExtent::Complete).unwrap_err(),
+            ReactionError::DifferentSubstance("NaOH".to_string()),
+        );
+        assert_eq!(solution.get_mol_by_name("NaOH").into_same_unit_with(&left).get_raw_num(), left.get_raw_num());
+    }
+
+    #[test]
+    fn nernst_potential() {
+        let substance = |formula: &str| Substance::from_formula(formula.to_string(), formula).unwrap();
+        let permanganate = HalfReaction::balance(
+            vec![substance("MnO4-"), substance("H+")],
+            vec![substance("Mn+2"), substance("H2O")],
+            Voltage::volt_from(1.507).set_sig_dig(4),
+        )
+        .unwrap();
+        println!("{}", permanganate);
+        assert_eq!(permanganate.get_electrons(), 5);
+        let iron = HalfReaction::balance(
+            vec![substance("Fe+3")],
+            vec![substance("Fe+2")],
+            Voltage::volt_from(0.771).set_sig_dig(3),
+        )
+        .unwrap();
+        assert_eq!(iron.get_electrons(), 1);
+
+        // Fe²⁺ の1割を酸化したところ
+        let mut solution = StockReagent::from_concentration(substance("Fe+3"), "0.00100 mol/L".parse().unwrap())
+            .dispense("10.0 mL".parse().unwrap());
+        solution.add_solution(
+            StockReagent::from_concentration(substance("Fe+2"), "0.00900 mol/L".parse().unwrap())
+                .dispense("10.0 mL".parse().unwrap()),
+        );
+        let e = iron.get_potential(&solution).unwrap();
+        println!("E = {} V", e.into_same_unit_with(&Voltage::volt_from(0.0)).get_digit().format_with_uncertainty());
+        assert!((e.get_volt_num() - (0.771 - 0.05916 * 9_f64.log10())).abs() < 1e-4);
+        // 37 ℃ では傾きが大きくなる
+        let warm = iron.get_potential_at(&solution, Temperature::kelvin_from(310.15)).unwrap();
+        assert!(warm < e);
+        // 最初の一滴の前はMn²⁺がないので電位は決まらない
+        let before = StockReagent::from_concentration(substance("MnO4-"), "0.0200 mol/L".parse().unwrap())
+            .dispense("10.0 mL".parse().unwrap());
+        assert_eq!(permanganate.get_potential(&before).unwrap_err(), RedoxError::NotInSolution("Mn+2".to_string()));
+        let mut ferric_only = StockReagent::from_concentration(substance("Fe+3"), "0.00100 mol/L".parse().unwrap())
+            .dispense("10.0 mL".parse().unwrap());
+        ferric_only.add_substance(Solid::create(substance("Fe+2"), "0 g".parse().unwrap(), 0.0.into()));
+        assert_eq!(iron.get_potential(&ferric_only).unwrap_err(), RedoxError::ZeroConcentration("Fe+2".to_string()));
+
+        // 1 mol/L H2SO4中 (pH 0) の当量点
+        let ph = DimSigDig::nodim_from(0.0).set_sig_dig(2);
+        let room = Temperature::celsius_from(25.0);
+        let e = equivalence_potential(&permanganate, &iron, ph, room);
+        println!("equivalence: {} V", e.get_digit());
+        assert!((e.get_volt_num() - (5.0 * 1.507 + 0.771) / 6.0).abs() < 1e-4);
+        let e_ph1 = equivalence_potential(&permanganate, &iron, DimSigDig::nodim_from(1.0), room);
+        assert!((e.get_volt_num() - e_ph1.get_volt_num() - 8.0 / 6.0 * 0.05916).abs() < 1e-4);
+        // pHの項の傾きは絶対温度に比例する
+        let warm_ph1 =
+            equivalence_potential(&permanganate, &iron, DimSigDig::nodim_from(1.0), Temperature::kelvin_from(318.15));
+        let ratio = (e.get_volt_num() - warm_ph1.get_volt_num()) / (e.get_volt_num() - e_ph1.get_volt_num());
+        assert!((ratio - 318.15 / 298.15).abs() < 1e-9);
+    }
+
+    #[test]
+    fn time_current_and_flow() {
+        // 0.500 A で 30.0 min 電解
+        let current = Current::milli_ampere_from(500.0).set_sig_dig(3);
+        let time = Time::minute_from(30.0).set_sig_dig(3);
+        let charge: Charge = current * time;
+        println!("Q = {}", charge.into_same_unit_with(&Charge::coulomb_from(0.0)));
+        assert!((charge.get_coulomb_num() - 900.0).abs() < 1e-9);
+        assert_eq!(format!("{}", charge.into_same_unit_with(&Charge::coulomb_from(0.0)).get_digit()), "900");
+        assert_eq!("1.00 mA".parse::<Current>().unwrap().get_ampere_num(), 0.001);
+        assert!(("900 C".parse::<Charge>().unwrap().get_coulomb_num() - 900.0).abs() < 1e-9);
+
+        // 0.0100 mol/L を 2.50 mL/min で 4.00 min 送る
+        let flow = Flow::milli_liter_per_minute_from(2.50).set_sig_dig(3);
+        // 表示はmL/s
+        assert_eq!(format!("{}", flow), "0.0417 [cm^3 s^-1]");
+        assert!((flow.get_milli_liter_per_minute_num() - 2.50).abs() < 1e-12);
+        assert!((flow.get_milli_liter_per_second_num() - 2.50 / 60.0).abs() < 1e-12);
+        let c: Concentration = "0.0100 mol/L".parse().unwrap();
+        let rate: Rate = (c * flow).into_same_unit_with(&Rate::mol_per_second_from(0.0));
+        assert!((rate.get_mol_per_second_num() - 0.0100 * 2.50e-3 / 60.0).abs() < 1e-15);
+        let delivered: Volume = flow * Time::minute_from(4.00).set_sig_dig(3);
+        assert_eq!(format!("{}", delivered.into_same_unit_with(&Volume::milli_liter_from(0.0)).get_digit()), "10.0");
+        let mol: Mol = rate * Time::second_from(240.0);
+        assert!((mol.into_same_unit_with(&Mol::mol_from(0.0)).get_raw_num() - 1.00e-4).abs() < 1e-12);
+
+        let room = Temperature::celsius_from(25.0);
+        assert!((room.get_kelvin_num() - 298.15).abs() < 1e-9);
+        assert!((Temperature::kelvin_from(310.15).get_celsius_num() - 37.0).abs() < 1e-9);
+        assert_eq!(Time::hour_from(1.5).get_minute_num(), 90.0);
+
+        // 不確かさを分やmL/minで与えるときはfrom_*で作る
+        let read: Time = Time::from_minutes("30.0(5)".parse().unwrap());
+        assert!((read.get_second_num() - 1800.0).abs() < 1e-9);
+        assert!((read.get_uncertainty().get_second_num() - 30.0).abs() < 1e-9);
+        assert_eq!(read.get_digit().get_sig_dig(), 3);
+        assert!((Time::from_hours("1.50".parse().unwrap()).get_minute_num() - 90.0).abs() < 1e-9);
+        let pump = Flow::from_milli_liter_per_minute("2.50(3)".parse().unwrap());
+        assert!((pump.get_milli_liter_per_minute_num() - 2.50).abs() < 1e-12);
+        assert!((pump.get_uncertainty().get_milli_liter_per_minute_num() - 0.03).abs() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic(expected = "different substances")]
+    fn same_name_different_substance() {
+        let catalog = SubstanceCatalog::from_toml_str(r#"
+            [[substance]]
+            name = "Mohr"
+            formula = "(NH4)2Fe(SO4)2·6H2O"
+            aliases = ["Mohr's salt"]
+        "#).unwrap();
+        let mut solution = Solution::new();
+        // 別名で引いても同じ物質としてまとまる
+        solution.add_substance(Solid::create(catalog.get("Mohr").unwrap(), 0.2.into(), 0.0.into()));
+        solution.add_substance(Solid::create(catalog.get("Mohr's salt").unwrap(), 0.2.into(), 0.0.into()));
+        assert_eq!(solution.get_concentration().len(), 1);
+        // 手で作った同名の物質は混ざらない
+        let typed = Substance::create("Mohr".to_string(), DimSigDig::molar_mass_from(392.1).set_sig_dig(4));
+        solution.add_substance(Solid::create(typed, 0.2.into(), 0.0.into()));
+    }
+
     #[test]
     fn mohr2() {
         let c = Beaker::<100>::new()
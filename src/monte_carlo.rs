@@ -0,0 +1,151 @@
+use crate::dim_sig_dig::*;
+
+use rand::{Rng, RngCore};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// 試行で値をばらつかせる乱数生成器
+// 溶液や器具に持たせて渡す. 既定 (Default) では何もばらつかせない
+// 複製は同じ生成器を共有するので, 一つの試行の中で溶液を分けても乱数の列は一つ
+#[derive(Clone, Default)]
+pub struct Sampler {
+    rng: Option<Rc<RefCell<dyn RngCore>>>,
+}
+
+impl Sampler {
+    pub fn new<R: RngCore + 'static>(rng: R) -> Self {
+        Self {
+            rng: Some(Rc::new(RefCell::new(rng))),
+        }
+    }
+    pub fn is_exact(&self) -> bool {
+        self.rng.is_none()
+    }
+    // 許容誤差は一様分布とみなし, 標準不確かさuに対して±√3uの範囲から引く
+    pub fn sample<
+        const N: i8,
+        const M: i8,
+        const L: i8,
+        const T: i8,
+        const THETA: i8,
+        const I: i8,
+        const J: i8,
+    >(
+        &self,
+        q: DimSigDig<N, M, L, T, THETA, I, J>,
+    ) -> DimSigDig<N, M, L, T, THETA, I, J> {
+        match self.rng.as_ref() {
+            Some(rng) => {
+                let half_width = 3_f64.sqrt();
+                q.shift_by_uncertainty(rng.borrow_mut().gen_range(-half_width..=half_width))
+            }
+            None => q,
+        }
+    }
+}
+
+impl std::fmt::Debug for Sampler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.rng {
+            Some(_) => write!(f, "Sampler(random)"),
+            None => write!(f, "Sampler(exact)"),
+        }
+    }
+}
+
+// GUM Supplement 1 のモンテカルロ法
+// 調製手順をクロージャで受け取り, 既存の器具の操作をそのまま繰り返す
+// 手順の最初の器具にset_samplerで生成器を渡すと, 溶液といっしょに後の器具へ伝わる
+pub struct MonteCarlo<R: RngCore + 'static> {
+    rng: R,
+    trials: usize,
+}
+
+impl<R: RngCore + 'static> MonteCarlo<R> {
+    pub fn new(rng: R, trials: usize) -> Self {
+        assert!(trials > 1);
+        Self { rng, trials }
+    }
+    pub fn run<F>(self, mut procedure: F) -> HashMap<String, ConcentrationDistribution>
+    where
+        F: FnMut(&Sampler) -> HashMap<String, Concentration>,
+    {
+        let sampler = Sampler::new(self.rng);
+        let mut samples: HashMap<String, Vec<f64>> = HashMap::new();
+        for _ in 0..self.trials {
+            for (name, c) in procedure(&sampler) {
+                samples.entry(name).or_default().push(c.get_molar_num());
+            }
+        }
+        samples
+            .into_iter()
+            .map(|(name, samples)| (name, ConcentrationDistribution::new(samples)))
+            .collect()
+    }
+}
+
+// 濃度の試行結果 (mol/L)
+#[derive(Debug, Clone)]
+pub struct ConcentrationDistribution {
+    samples: Vec<f64>,
+}
+
+impl ConcentrationDistribution {
+    fn new(mut samples: Vec<f64>) -> Self {
+        samples.sort_by(|a, b| a.total_cmp(b));
+        Self { samples }
+    }
+    pub fn get_samples(&self) -> &[f64] {
+        &self.samples
+    }
+    fn raw_mean(&self) -> f64 {
+        self.samples.iter().sum::<f64>() / self.samples.len() as f64
+    }
+    fn raw_std_dev(&self) -> f64 {
+        let mean = self.raw_mean();
+        let var = self.samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>()
+            / (self.samples.len() - 1) as f64;
+        var.sqrt()
+    }
+    // 有効数字は標準偏差の最初の桁まで
+    pub fn mean(&self) -> Concentration {
+        DimSigDig::molar_from(self.raw_mean())
+            .set_uncertainty(self.raw_std_dev())
+            .fit_sig_dig_to_uncertainty()
+    }
+    pub fn std_dev(&self) -> Concentration {
+        DimSigDig::molar_from(self.raw_std_dev())
+    }
+    // p: 0.0 ~ 1.0, 線形補間
+    pub fn percentile(&self, p: f64) -> Concentration {
+        assert!((0.0..=1.0).contains(&p));
+        let position = p * (self.samples.len() - 1) as f64;
+        let lower = self.samples[position.floor() as usize];
+        let upper = self.samples[position.ceil() as usize];
+        DimSigDig::molar_from(lower + (upper - lower) * position.fract())
+    }
+    // 確率pの対称な包含区間
+    pub fn coverage_interval(&self, p: f64) -> (Concentration, Concentration) {
+        let tail = (1.0 - p) / 2.0;
+        (self.percentile(tail), self.percentile(1.0 - tail))
+    }
+}
+
+#[test]
+fn nested_run_test() {
+    use rand::{rngs::StdRng, SeedableRng};
+    let c = Concentration::molar_from(1.000).set_uncertainty(0.010);
+    let outer = |nested: bool| {
+        MonteCarlo::new(StdRng::seed_from_u64(1), 50).run(|sampler| {
+            if nested {
+                MonteCarlo::new(StdRng::seed_from_u64(2), 5)
+                    .run(|inner| [("inner".to_string(), inner.sample(c))].into_iter().collect());
+            }
+            [("outer".to_string(), sampler.sample(c))].into_iter().collect()
+        })
+    };
+    // 内側のrunは外側の生成器を消費しない
+    assert_eq!(outer(true)["outer"].get_samples(), outer(false)["outer"].get_samples());
+    assert_eq!(Sampler::default().sample(c), c);
+}
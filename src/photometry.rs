@@ -0,0 +1,190 @@
+use crate::dim_sig_dig::*;
+use crate::planner::Standard;
+
+// 吸光度 A = -log10 T
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Absorbance(NoDim);
+
+// 透過率 T (0 ~ 1)
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Transmittance(NoDim);
+
+// 計算の途中で付いた10の累乗を数値に戻しておく
+fn plain(x: NoDim) -> NoDim {
+    x.into_same_unit_with(&DimSigDig::nodim_from(0.0))
+}
+
+impl Absorbance {
+    pub fn new(a: NoDim) -> Self {
+        Self(plain(a))
+    }
+    pub fn get_value(&self) -> NoDim {
+        self.0
+    }
+    // u(T) = T ln10 u(A)
+    pub fn to_transmittance(self) -> Transmittance {
+        let a = self.0.get_raw_num();
+        let t = 10_f64.powf(-a);
+        let u = t * 10_f64.ln() * self.0.get_uncertainty().get_raw_num();
+        Transmittance(
+            DimSigDig::nodim_from(t)
+                .set_uncertainty(u)
+                .fit_sig_dig_to_uncertainty(),
+        )
+    }
+}
+
+impl Transmittance {
+    pub fn new(t: NoDim) -> Self {
+        let t = plain(t);
+        assert!(t.get_raw_num() > 0.0 && t.get_raw_num() <= 1.0);
+        Self(t)
+    }
+    pub fn from_percent(percent: NoDim) -> Self {
+        Self::new(percent * DimSigDig::nodim_from(0.01).set_uncertainty(0.0))
+    }
+    pub fn get_value(&self) -> NoDim {
+        self.0
+    }
+    pub fn get_percent(&self) -> NoDim {
+        plain(self.0 * DimSigDig::nodim_from(100.0).set_uncertainty(0.0))
+    }
+    // u(A) = u(T) / (T ln10)
+    pub fn to_absorbance(self) -> Absorbance {
+        let t = self.0.get_raw_num();
+        let u = self.0.get_uncertainty().get_raw_num() / (t * 10_f64.ln());
+        Absorbance(
+            DimSigDig::nodim_from(-t.log10())
+                .set_uncertainty(u)
+                .fit_sig_dig_to_uncertainty(),
+        )
+    }
+}
+
+impl std::fmt::Display for Absorbance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "A = {}", self.0.get_digit())
+    }
+}
+
+impl std::fmt::Display for Transmittance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "T = {} %", self.get_percent().get_digit())
+    }
+}
+
+// 直線性の保てる範囲から外れた吸光度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RangeWarning {
+    // 低すぎて読みとりの誤差が大きい
+    TooLow(Absorbance),
+    // 高すぎて迷光などで直線から外れる
+    TooHigh(Absorbance),
+}
+
+impl std::fmt::Display for RangeWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooLow(a) => write!(f, "{} is below the linear range", a),
+            Self::TooHigh(a) => write!(f, "{} is above the linear range", a),
+        }
+    }
+}
+
+// A = εbc
+#[derive(Debug, Clone, Copy)]
+pub struct BeerLambert {
+    absorptivity: MolarAbsorptivity,
+    path_length: Length,
+    linear_range: (f64, f64),
+}
+
+impl BeerLambert {
+    // 光路長は1 cmのセルとする
+    pub fn new(absorptivity: MolarAbsorptivity) -> Self {
+        Self {
+            absorptivity,
+            path_length: Length::centi_meter_from(1.0).set_uncertainty(0.0),
+            linear_range: (0.1, 1.0),
+        }
+    }
+    pub fn set_path_length(self, path_length: Length) -> Self {
+        Self {
+            path_length,
+            ..self
+        }
+    }
+    pub fn set_linear_range(self, low: f64, high: f64) -> Self {
+        assert!(0.0 <= low && low < high);
+        Self {
+            linear_range: (low, high),
+            ..self
+        }
+    }
+    pub fn get_absorptivity(&self) -> MolarAbsorptivity {
+        self.absorptivity
+    }
+    pub fn get_path_length(&self) -> Length {
+        self.path_length
+    }
+    pub fn absorbance_of(&self, c: Concentration) -> Absorbance {
+        Absorbance::new(self.absorptivity * self.path_length * c)
+    }
+    pub fn concentration_of(&self, a: Absorbance) -> Concentration {
+        (a.0 / (self.absorptivity * self.path_length)).convert_to_molar()
+    }
+    // 検量線の傾きからモル吸光係数を求める (ε = slope / b)
+    pub fn from_sensitivity(slope: Sensitivity, path_length: Length) -> Self {
+        let absorptivity = slope / path_length;
+        let absorptivity = absorptivity.into_same_unit_with(
+            &MolarAbsorptivity::liter_per_mol_per_centi_meter_from(0.0),
+        );
+        Self::new(absorptivity).set_path_length(path_length)
+    }
+    pub fn check_range(&self, a: Absorbance) -> Option<RangeWarning> {
+        let value = a.0.get_raw_num();
+        if value < self.linear_range.0 {
+            Some(RangeWarning::TooLow(a))
+        } else if value > self.linear_range.1 {
+            Some(RangeWarning::TooHigh(a))
+        } else {
+            None
+        }
+    }
+    // 標準溶液の系列で読めるはずの吸光度
+    pub fn predict_readings(&self, standards: &[Standard]) -> Vec<(Absorbance, Option<RangeWarning>)> {
+        standards
+            .iter()
+            .map(|s| self.absorbance_of(s.get_concentration()))
+            .map(|a| (a, self.check_range(a)))
+            .collect()
+    }
+}
+
+#[test]
+fn beer_lambert_test() {
+    // Fe(II)-1,10-フェナントロリン錯体, 510 nm
+    let fe_phen = BeerLambert::new(
+        MolarAbsorptivity::liter_per_mol_per_centi_meter_from(1.11e4).set_sig_dig(3),
+    );
+    let c: Concentration = "2.562e-5 mol/L".parse().unwrap();
+    let a = fe_phen.absorbance_of(c);
+    println!("{} ({})", a, a.to_transmittance());
+    assert!(a.get_value().is_close_to(&DimSigDig::nodim_from(0.284)));
+    assert!(fe_phen.concentration_of(a).is_close_to(&c));
+    assert_eq!(fe_phen.check_range(a), None);
+
+    // 2 mmのセルなら吸光度は1/5
+    let thin = fe_phen.set_path_length(Length::milli_meter_from(2.000).set_sig_dig(4));
+    let a_thin = thin.absorbance_of(c);
+    assert!(a_thin.get_value().is_close_to(&DimSigDig::nodim_from(0.0569)));
+    assert!(matches!(thin.check_range(a_thin), Some(RangeWarning::TooLow(_))));
+
+    let t = Transmittance::from_percent("52.0".parse().unwrap());
+    assert!(t.to_absorbance().get_value().is_close_to(&DimSigDig::nodim_from(0.284)));
+    assert!(t.to_absorbance().to_transmittance().get_value().is_close_to(&t.get_value()));
+
+    let slope: Sensitivity = "1.11e4 L/mol".parse().unwrap();
+    let from_slope = BeerLambert::from_sensitivity(slope, Length::centi_meter_from(1.00));
+    assert!((from_slope.get_absorptivity().get_liter_per_mol_per_centi_meter_num() - 1.11e4).abs() < 1.0);
+}
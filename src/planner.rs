@@ -0,0 +1,663 @@
+use crate::apparatus::*;
+use crate::dim_sig_dig::*;
+use crate::substance::*;
+
+// 用意のある器具の容量 (mL)
+pub const BEAKER_SIZES: [usize; 6] = [50, 100, 200, 300, 500, 1000];
+pub const FLASK_SIZES: [usize; 9] = [10, 20, 25, 50, 100, 200, 250, 500, 1000];
+pub const PIPETTE_SIZES: [usize; 7] = [1, 2, 5, 10, 20, 25, 50];
+pub const CYLINDER_SIZES: [usize; 6] = [10, 25, 50, 100, 200, 500];
+
+// 実行時の容量から型引数つきの器具を選ぶ
+macro_rules! with_size {
+    ($size:expr, $V:ident, [$($v:literal),*], $body:expr) => {
+        match $size {
+            $($v => {
+                const $V: usize = $v;
+                $body
+            })*
+            size => panic!("no glassware of {} mL", size),
+        }
+    };
+}
+macro_rules! with_beaker {
+    ($size:expr, $V:ident, $body:expr) => {
+        with_size!($size, $V, [50, 100, 200, 300, 500, 1000], $body)
+    };
+}
+macro_rules! with_flask {
+    ($size:expr, $V:ident, $body:expr) => {
+        with_size!($size, $V, [10, 20, 25, 50, 100, 200, 250, 500, 1000], $body)
+    };
+}
+macro_rules! with_pipette {
+    ($size:expr, $V:ident, $body:expr) => {
+        with_size!($size, $V, [1, 2, 5, 10, 20, 25, 50], $body)
+    };
+}
+macro_rules! with_cylinder {
+    ($size:expr, $V:ident, $body:expr) => {
+        with_size!($size, $V, [10, 25, 50, 100, 200, 500], $body)
+    };
+}
+
+#[derive(Debug, Clone)]
+pub enum Step {
+    Weigh { substance: Substance, mass: Mass },
+    // 保存溶液から始める
+    Stock { reagent: StockReagent },
+    // ビーカーで溶かしておよそvolumeにする
+    Dissolve { beaker: usize, volume: Volume },
+    // 保存溶液をメスシリンダーではかってビーカーに加える
+    Measure { reagent: StockReagent, cylinder: usize, volume: Volume },
+    TransferToFlask { flask: usize },
+    FillUp,
+    // いまのメスフラスコからtimes回とって新しいメスフラスコに入れる
+    Pipette { pipette: usize, times: usize, flask: usize },
+}
+
+impl std::fmt::Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Weigh { substance, mass } => {
+                let mass = mass.into_same_unit_with(&DimSigDig::gram_from(0.0));
+                write!(f, "weigh {} g of {}", mass.get_digit(), substance.get_name())
+            }
+            Self::Stock { reagent } => write!(
+                f,
+                "take the {} {} stock solution",
+                reagent.get_concentration(),
+                reagent.get_name()
+            ),
+            Self::Dissolve { beaker, volume } => write!(
+                f,
+                "dissolve in about {} mL of water in a {} mL beaker",
+                volume.into_same_unit_with(&Volume::milli_liter_from(0.0)).get_digit(),
+                beaker
+            ),
+            Self::Measure { reagent, cylinder, volume } => write!(
+                f,
+                "add {} mL of the {} stock solution with a {} mL measuring cylinder",
+                volume.into_same_unit_with(&Volume::milli_liter_from(0.0)).get_digit(),
+                reagent.get_name(),
+                cylinder
+            ),
+            Self::TransferToFlask { flask } => {
+                write!(f, "transfer into a {} mL volumetric flask", flask)
+            }
+            Self::FillUp => write!(f, "fill up to the mark"),
+            Self::Pipette { pipette, times, flask } => write!(
+                f,
+                "pipette {} mL × {} into a {} mL volumetric flask",
+                pipette, times, flask
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Vessel {
+    Beaker(usize),
+    Flask(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct PreparationPlan {
+    name: String,
+    steps: Vec<Step>,
+    expected: Concentration,
+}
+
+impl PreparationPlan {
+    pub(crate) fn create(name: String, steps: Vec<Step>) -> Self {
+        let mut plan = Self {
+            name,
+            steps,
+            expected: 0.0.into(),
+        };
+        plan.expected = plan
+            .execute()
+            .get_concentration()
+            .get(&plan.name)
+            .cloned()
+            .unwrap_or(0.0.into())
+            .convert_to_molar();
+        plan
+    }
+    pub fn get_steps(&self) -> &[Step] {
+        &self.steps
+    }
+    // 手順どおりに調製したときの濃度 (有効数字と不確かさつき)
+    pub fn get_expected_concentration(&self) -> Concentration {
+        self.expected
+    }
+    // 既存の器具の操作で手順を実行し, 最後のメスフラスコの中身を返す
+    pub fn execute(&self) -> Solution {
+        let mut balance: Vec<Solid> = vec![];
+        let mut stock: Option<StockReagent> = None;
+        let mut current: Option<(Vessel, Solution)> = None;
+        for step in self.steps.iter() {
+            current = Some(match (step, current.take()) {
+                (Step::Weigh { substance, mass }, current) => {
+                    balance.push(Solid::create(substance.clone(), *mass, 0.0.into()));
+                    match current {
+                        Some(current) => current,
+                        None => continue,
+                    }
+                }
+                (Step::Stock { reagent }, None) => {
+                    stock = Some(reagent.clone());
+                    continue;
+                }
+                (Step::Pipette { pipette, times, flask }, None) => {
+                    let reagent = stock.as_ref().expect("there is no stock solution");
+                    let solution = with_pipette!(*pipette, P, with_flask!(*flask, F, {
+                        let mut pipette = Pipette::<P>::new();
+                        let mut target = VolumetricFlask::<F>::new();
+                        for _ in 0..*times {
+                            pipette.aspirate_reagent_mut(reagent);
+                            pipette.into_flask_mut(&mut target);
+                        }
+                        target.get_solution().clone()
+                    }));
+                    (Vessel::Flask(*flask), solution)
+                }
+                (Step::Dissolve { beaker, volume }, None) => {
+                    let solution = with_beaker!(*beaker, B, {
+                        balance
+                            .drain(..)
+                            .fold(Beaker::<B>::new(), |beaker, solid| beaker.add_substance(solid))
+                            .fillup_to(*volume)
+                            .get_solution()
+                            .clone()
+                    });
+                    (Vessel::Beaker(*beaker), solution)
+                }
+                (
+                    Step::Measure { reagent, cylinder, volume },
+                    Some((Vessel::Beaker(beaker), solution)),
+                ) => {
+                    let solution = with_beaker!(beaker, B, with_cylinder!(*cylinder, C, {
+                        MeasuringCylinder::<C>::new()
+                            .measure(reagent, *volume)
+                            .into_beaker(Beaker::<B>::new().add_solution(solution))
+                            .get_solution()
+                            .clone()
+                    }));
+                    (Vessel::Beaker(beaker), solution)
+                }
+                (Step::TransferToFlask { flask }, Some((Vessel::Beaker(beaker), solution))) => {
+                    let solution = with_beaker!(beaker, B, with_flask!(*flask, F, {
+                        Beaker::<B>::new()
+                            .add_solution(solution)
+                            .into_volumetric_flask(VolumetricFlask::<F>::new())
+                            .get_solution()
+                            .clone()
+                    }));
+                    (Vessel::Flask(*flask), solution)
+                }
+                (Step::FillUp, Some((Vessel::Flask(flask), solution))) => {
+                    let solution = with_flask!(flask, F, {
+                        VolumetricFlask::<F>::new()
+                            .add_solution(solution)
+                            .fillup()
+                            .get_solution()
+                            .clone()
+                    });
+                    (Vessel::Flask(flask), solution)
+                }
+                (
+                    Step::Pipette { pipette, times, flask },
+                    Some((Vessel::Flask(source), solution)),
+                ) => {
+                    let solution = with_flask!(source, S, with_pipette!(*pipette, P, {
+                        with_flask!(*flask, F, {
+                            let mut source = VolumetricFlask::<S>::new().add_solution(solution);
+                            let mut pipette = Pipette::<P>::new();
+                            let mut target = VolumetricFlask::<F>::new();
+                            for _ in 0..*times {
+                                source.into_pipette_mut(&mut pipette);
+                                pipette.into_flask_mut(&mut target);
+                            }
+                            target.get_solution().clone()
+                        })
+                    }));
+                    (Vessel::Flask(*flask), solution)
+                }
+                (step, current) => panic!("cannot {} with {:?}", step, current.map(|c| c.0)),
+            });
+        }
+        match current {
+            Some((Vessel::Flask(_), solution)) => solution,
+            _ => panic!("the plan does not end in a volumetric flask"),
+        }
+    }
+}
+
+impl std::fmt::Display for PreparationPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            writeln!(f, "{}. {}", i + 1, step)?;
+        }
+        write!(f, "-> {} {}", self.name, self.expected.format_with_uncertainty())
+    }
+}
+
+// 目的の濃度の溶液を固体から調製する手順を考える
+// はかりとる量が少なすぎるときは濃い溶液を作ってから希釈する
+#[derive(Debug, Clone, Copy)]
+pub struct Planner {
+    // 天秤の読み取り限度
+    readability: Mass,
+    // これより少ない量ははかりとらない
+    min_mass: Mass,
+}
+
+impl Planner {
+    pub fn new() -> Self {
+        Self {
+            readability: DimSigDig::gram_from(0.0001),
+            min_mass: DimSigDig::gram_from(0.1),
+        }
+    }
+    pub fn set_readability(self, readability: Mass) -> Self {
+        Self { readability, ..self }
+    }
+    pub fn set_min_mass(self, min_mass: Mass) -> Self {
+        Self { min_mass, ..self }
+    }
+    fn to_gram(m: Mass) -> f64 {
+        m.into_same_unit_with(&DimSigDig::gram_from(0.0)).get_raw_num()
+    }
+    // 天秤で読める桁に丸める
+    pub(crate) fn round_mass(&self, grams: f64) -> Mass {
+        let decimals = (-Self::to_gram(self.readability).log10().floor()).max(0.0) as i32;
+        let rounded = (grams * 10_f64.powi(decimals)).round() / 10_f64.powi(decimals);
+        // 有効数字は最上位の桁から読める桁まで (0 なら小数点以下の桁数)
+        let sig_dig = match rounded == 0.0 {
+            true => decimals.max(1),
+            false => (rounded.abs().log10().floor() as i32 + 1 + decimals).max(1),
+        };
+        Mass::gram_from(rounded).set_sig_dig(sig_dig as usize)
+    }
+    // 濃度c, 体積v (mL) にするための質量 (g)
+    fn required_grams(substance: &Substance, c: Concentration, v: f64) -> f64 {
+        let mol = c * Volume::milli_liter_from(v);
+        let mass = mol * substance.get_molar_mass() / substance.get_purity();
+        Self::to_gram(mass)
+    }
+    // 溶かすときはメスフラスコの半分ほどの水を使う
+    // 溶けにくいものは溶解度の1.2倍の余裕をもたせて水を増やす (メスフラスコの8割まで)
+    fn dissolve_steps(&self, substance: &Substance, grams: f64, flask: usize) -> Option<Vec<Step>> {
+        let required = match substance.get_solubility() {
+            Some(solubility) => {
+                let g_per_ml = solubility
+                    .into_same_unit_with(&MassConcentration::gram_per_milli_liter_from(0.0))
+                    .get_raw_num();
+                (grams / g_per_ml * 1.2 / 10.0).ceil() * 10.0
+            }
+            None => 0.0,
+        };
+        let milli_liter = (flask as f64 / 2.0).max(required);
+        if milli_liter > flask as f64 * 0.8 {
+            return None;
+        }
+        let volume = Volume::milli_liter_from(milli_liter).set_sig_dig(2);
+        let beaker = BEAKER_SIZES.into_iter().find(|b| *b as f64 > milli_liter)?;
+        Some(vec![
+            Step::Weigh {
+                substance: substance.clone(),
+                mass: self.round_mass(grams),
+            },
+            Step::Dissolve { beaker, volume },
+            Step::TransferToFlask { flask },
+            Step::FillUp,
+        ])
+    }
+    pub fn plan(&self, substance: &Substance, c: Concentration, v: Volume) -> Option<PreparationPlan> {
+        let v = v.into_same_unit_with(&Volume::milli_liter_from(0.0)).get_raw_num();
+        let flask = FLASK_SIZES.into_iter().find(|f| *f as f64 >= v)?;
+        let min_grams = Self::to_gram(self.min_mass);
+
+        let grams = Self::required_grams(substance, c, flask as f64);
+        if grams >= min_grams {
+            let steps = self.dissolve_steps(substance, grams, flask)?;
+            return Some(PreparationPlan::create(substance.get_name().to_string(), steps));
+        }
+
+        // 一段希釈: 許容誤差の小さい大きめのホールピペットを優先し, 次に試薬の少ないもの
+        let (pipette, stock, grams) = PIPETTE_SIZES
+            .into_iter()
+            .filter(|p| *p < flask)
+            .flat_map(|p| FLASK_SIZES.into_iter().filter(move |s| p < *s).map(move |s| (p, s)))
+            .map(|(p, s)| {
+                let c_stock = c * DimSigDig::nodim_from(flask as f64 / p as f64);
+                (p, s, Self::required_grams(substance, c_stock, s as f64))
+            })
+            .filter(|(_, _, grams)| *grams >= min_grams)
+            .min_by(|a, b| b.0.cmp(&a.0).then(a.2.total_cmp(&b.2)))?;
+        let mut steps = self.dissolve_steps(substance, grams, stock)?;
+        steps.push(Step::Pipette {
+            pipette,
+            times: 1,
+            flask,
+        });
+        steps.push(Step::FillUp);
+        Some(PreparationPlan::create(substance.get_name().to_string(), steps))
+    }
+}
+
+impl Default for Planner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 一段の希釈: pipette mLとってflask mLにする
+#[derive(Debug, Clone, Copy)]
+struct Stage {
+    pipette: usize,
+    flask: usize,
+}
+
+impl Stage {
+    fn factor(&self) -> f64 {
+        self.flask as f64 / self.pipette as f64
+    }
+    fn relative_variance(&self) -> f64 {
+        let u_p = with_pipette!(self.pipette, P, Pipette::<P>::get_capacity()).get_relative_uncertainty();
+        let u_f = with_flask!(self.flask, F, VolumetricFlask::<F>::get_capacity()).get_relative_uncertainty();
+        u_p.powi(2) + u_f.powi(2)
+    }
+}
+
+// 保存溶液から目的の濃度まで, 手元のメスフラスコとホールピペットで薄める手順を考える
+// 段数が少ないもの, 保存溶液の使用量が少ないもの, 相対不確かさが小さいものの順に選ぶ
+#[derive(Debug, Clone)]
+pub struct DilutionPlanner {
+    flasks: Vec<usize>,
+    pipettes: Vec<usize>,
+    max_stages: usize,
+    // 希釈倍率のずれの許容範囲 (相対)
+    tolerance: f64,
+}
+
+impl DilutionPlanner {
+    pub fn new(flasks: &[usize], pipettes: &[usize]) -> Self {
+        for f in flasks {
+            assert!(FLASK_SIZES.contains(f), "no volumetric flask of {} mL", f);
+        }
+        for p in pipettes {
+            assert!(PIPETTE_SIZES.contains(p), "no pipette of {} mL", p);
+        }
+        Self {
+            flasks: flasks.to_vec(),
+            pipettes: pipettes.to_vec(),
+            max_stages: 3,
+            tolerance: 0.001,
+        }
+    }
+    pub fn set_max_stages(self, max_stages: usize) -> Self {
+        Self { max_stages, ..self }
+    }
+    pub fn set_tolerance(self, tolerance: f64) -> Self {
+        Self { tolerance, ..self }
+    }
+    fn stages(&self) -> Vec<Stage> {
+        self.pipettes
+            .iter()
+            .flat_map(|p| self.flasks.iter().map(move |f| (*p, *f)))
+            .filter(|(p, f)| p < f)
+            .map(|(pipette, flask)| Stage { pipette, flask })
+            .collect()
+    }
+    // 倍率の積がfactorになる組み合わせを深さ優先で探す
+    // 二段目からは前の段のメスフラスコからとるので, ホールピペットはそれより小さくなければならない
+    fn search(&self, stages: &[Stage], factor: f64, chain: &mut Vec<Stage>, found: &mut Vec<Vec<Stage>>) {
+        let product: f64 = chain.iter().map(|s| s.factor()).product();
+        if !chain.is_empty() && (product / factor - 1.0).abs() <= self.tolerance {
+            found.push(chain.clone());
+            return;
+        }
+        if chain.len() >= self.max_stages || product > factor * (1.0 + self.tolerance) {
+            return;
+        }
+        for stage in stages {
+            if chain.last().is_some_and(|last| stage.pipette >= last.flask) {
+                continue;
+            }
+            chain.push(*stage);
+            self.search(stages, factor, chain, found);
+            chain.pop();
+        }
+    }
+    pub fn plan(&self, stock: &StockReagent, target: Concentration) -> Option<PreparationPlan> {
+        let factor = (stock.get_concentration() / target).get_raw_num();
+        if factor <= 1.0 {
+            return None;
+        }
+        let mut found = vec![];
+        self.search(&self.stages(), factor, &mut vec![], &mut found);
+        let relative_variance =
+            |chain: &Vec<Stage>| chain.iter().map(|s| s.relative_variance()).sum::<f64>();
+        let best = found.into_iter().min_by(|a, b| {
+            a.len()
+                .cmp(&b.len())
+                .then(a[0].pipette.cmp(&b[0].pipette))
+                .then(relative_variance(a).total_cmp(&relative_variance(b)))
+        })?;
+        let mut steps = vec![Step::Stock {
+            reagent: stock.clone(),
+        }];
+        for stage in best {
+            steps.push(Step::Pipette {
+                pipette: stage.pipette,
+                times: 1,
+                flask: stage.flask,
+            });
+            steps.push(Step::FillUp);
+        }
+        Some(PreparationPlan::create(stock.get_name(), steps))
+    }
+}
+
+impl Default for DilutionPlanner {
+    fn default() -> Self {
+        Self::new(&FLASK_SIZES, &PIPETTE_SIZES)
+    }
+}
+
+// 検量線用の標準溶液1本分
+#[derive(Debug, Clone)]
+pub struct Standard {
+    target: Concentration,
+    // pipette mLをtimes回とってflask mLにする (times == 0 は空試験)
+    pipette: usize,
+    times: usize,
+    flask: usize,
+    solution: Solution,
+    concentration: Concentration,
+}
+
+impl Standard {
+    pub fn get_target(&self) -> Concentration {
+        self.target
+    }
+    pub fn get_pipette(&self) -> usize {
+        self.pipette
+    }
+    pub fn get_times(&self) -> usize {
+        self.times
+    }
+    pub fn get_flask(&self) -> usize {
+        self.flask
+    }
+    pub fn get_solution(&self) -> &Solution {
+        &self.solution
+    }
+    // 調製した濃度 (有効数字と不確かさつき)
+    pub fn get_concentration(&self) -> Concentration {
+        self.concentration
+    }
+}
+
+// 保存溶液から濃度の違う標準溶液を一揃い作る
+// どの標準も同じ大きさのメスフラスコで作る. メスフラスコは小さい方から試し,
+// すべての標準が作れて使う保存溶液が残量に収まる最初のものを選ぶ
+// 各標準のとり方は相対不確かさが最も小さいもの
+#[derive(Debug, Clone)]
+pub struct StandardSeries {
+    targets: Vec<Concentration>,
+    flasks: Vec<usize>,
+    pipettes: Vec<usize>,
+    max_aliquots: usize,
+    // 目標濃度とのずれの許容範囲 (相対)
+    tolerance: f64,
+}
+
+impl StandardSeries {
+    pub fn new(targets: Vec<Concentration>) -> Self {
+        Self {
+            targets: targets.into_iter().map(|c| c.convert_to_molar()).collect(),
+            flasks: FLASK_SIZES.to_vec(),
+            pipettes: PIPETTE_SIZES.to_vec(),
+            max_aliquots: 5,
+            tolerance: 0.001,
+        }
+    }
+    // lowからhighまで等間隔にcount本
+    pub fn linear(low: Concentration, high: Concentration, count: usize) -> Self {
+        assert!(count >= 2);
+        let (low, high) = (low.get_molar_num(), high.get_molar_num());
+        let targets = (0..count)
+            .map(|i| low + (high - low) * i as f64 / (count - 1) as f64)
+            .map(DimSigDig::molar_from)
+            .collect();
+        Self::new(targets)
+    }
+    // lowからhighまで等比でcount本
+    pub fn logarithmic(low: Concentration, high: Concentration, count: usize) -> Self {
+        assert!(count >= 2);
+        let (low, high) = (low.get_molar_num(), high.get_molar_num());
+        assert!(low > 0.0);
+        let targets = (0..count)
+            .map(|i| low * (high / low).powf(i as f64 / (count - 1) as f64))
+            .map(DimSigDig::molar_from)
+            .collect();
+        Self::new(targets)
+    }
+    pub fn set_flasks(self, flasks: &[usize]) -> Self {
+        for f in flasks {
+            assert!(FLASK_SIZES.contains(f), "no volumetric flask of {} mL", f);
+        }
+        Self {
+            flasks: flasks.to_vec(),
+            ..self
+        }
+    }
+    pub fn set_pipettes(self, pipettes: &[usize]) -> Self {
+        for p in pipettes {
+            assert!(PIPETTE_SIZES.contains(p), "no pipette of {} mL", p);
+        }
+        Self {
+            pipettes: pipettes.to_vec(),
+            ..self
+        }
+    }
+    pub fn set_max_aliquots(self, max_aliquots: usize) -> Self {
+        Self {
+            max_aliquots,
+            ..self
+        }
+    }
+    pub fn set_tolerance(self, tolerance: f64) -> Self {
+        Self { tolerance, ..self }
+    }
+    pub fn get_targets(&self) -> &[Concentration] {
+        &self.targets
+    }
+    // 目標濃度ごとの (pipette, times) を選ぶ. 同じピペットなら回数の多い方が不確かさは小さい
+    fn aliquot(&self, stock: f64, target: f64, flask: usize) -> Option<(usize, usize)> {
+        if target == 0.0 {
+            return Some((0, 0));
+        }
+        self.pipettes
+            .iter()
+            .flat_map(|p| (1..=self.max_aliquots).map(move |n| (*p, n)))
+            .filter(|(p, n)| {
+                let c = stock * (p * n) as f64 / flask as f64;
+                (c / target - 1.0).abs() <= self.tolerance
+            })
+            .min_by(|(p1, n1), (p2, n2)| {
+                let u = |p: usize, n: usize| {
+                    Stage { pipette: p, flask }.relative_variance() / n as f64
+                };
+                u(*p1, *n1).total_cmp(&u(*p2, *n2))
+            })
+    }
+    // 保存溶液と残量から, メスフラスコの大きさと各標準のとり方を決める
+    pub fn design(&self, stock: Concentration, available: Volume) -> Option<(usize, Vec<(usize, usize)>)> {
+        let stock = stock.get_molar_num();
+        let available = available
+            .into_same_unit_with(&Volume::milli_liter_from(0.0))
+            .get_raw_num();
+        let mut flasks = self.flasks.clone();
+        flasks.sort();
+        flasks.into_iter().find_map(|flask| {
+            let aliquots = self
+                .targets
+                .iter()
+                .map(|c| self.aliquot(stock, c.get_molar_num(), flask))
+                .collect::<Option<Vec<_>>>()?;
+            let used: usize = aliquots.iter().map(|(p, n)| p * n).sum();
+            ((used as f64) < available).then_some((flask, aliquots))
+        })
+    }
+    // 保存溶液のメスフラスコからピペットでとり, 標準溶液を作る
+    pub fn prepare<const S: usize>(
+        &self,
+        name: &str,
+        stock: &mut VolumetricFlask<S>,
+    ) -> Option<Vec<Standard>> {
+        let (flask, aliquots) = self.design(
+            stock.get_concentration_by_name(name),
+            stock.get_solution().get_volume(),
+        )?;
+        let standards = self
+            .targets
+            .iter()
+            .zip(aliquots)
+            .map(|(target, (pipette, times))| {
+                let solution = with_flask!(flask, F, {
+                    let mut target = VolumetricFlask::<F>::new();
+                    if times > 0 {
+                        with_pipette!(pipette, P, {
+                            let mut pipette = Pipette::<P>::new();
+                            for _ in 0..times {
+                                stock.into_pipette_mut(&mut pipette);
+                                pipette.into_flask_mut(&mut target);
+                            }
+                        });
+                    }
+                    target.fillup().get_solution().clone()
+                });
+                let concentration = solution
+                    .get_concentration()
+                    .get(name)
+                    .cloned()
+                    .unwrap_or(0.0.into())
+                    .convert_to_molar()
+                    .normalized();
+                Standard {
+                    target: *target,
+                    pipette,
+                    times,
+                    flask,
+                    solution,
+                    concentration,
+                }
+            })
+            .collect();
+        Some(standards)
+    }
+}
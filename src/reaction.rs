@@ -0,0 +1,489 @@
+use crate::dim_sig_dig::*;
+use crate::substance::*;
+
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReactionError {
+    MissingFormula(String),
+    // 係数をどう選んでも原子と電荷がそろわない
+    Unbalanceable,
+    // 係数の比が一通りに決まらない (独立な反応が混ざっている)
+    Ambiguous,
+    // 反応物が与えられていない
+    MissingReactant(String),
+    // 溶媒のほかに反応物がない (2H2O → 2H2 + O2 など)
+    NoReactant,
+    // 物質量が数でない
+    InvalidAmount(String),
+    // 溶けている塩のイオンとしてしかない. 反応式は塩で書く
+    OnlyAsIon(String),
+    // 同じ名前の溶質が別の物質として入っている
+    DifferentSubstance(String),
+}
+
+impl std::fmt::Display for ReactionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingFormula(name) => write!(f, "{} has no formula", name),
+            Self::Unbalanceable => write!(f, "the equation cannot be balanced"),
+            Self::Ambiguous => write!(f, "the coefficients are not uniquely determined"),
+            Self::MissingReactant(name) => write!(f, "{} is not given", name),
+            Self::NoReactant => write!(f, "there is no reactant other than the solvent"),
+            Self::InvalidAmount(name) => write!(f, "the amount of {} is not a number", name),
+            Self::OnlyAsIon(name) => {
+                write!(f, "{} is dissolved only as an ion of a salt; write the reaction with the salt", name)
+            }
+            Self::DifferentSubstance(name) => {
+                write!(f, "{} in the solution is a different substance with the same name", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReactionError {}
+
+// 化学反応式. 係数は整数
+#[derive(Debug, Clone)]
+pub struct Reaction {
+    reactants: Vec<(Substance, u32)>,
+    products: Vec<(Substance, u32)>,
+}
+
+impl Reaction {
+    pub fn new(reactants: Vec<(Substance, u32)>, products: Vec<(Substance, u32)>) -> Self {
+        assert!(!reactants.is_empty() && !products.is_empty());
+        assert!(reactants.iter().chain(products.iter()).all(|(_, n)| *n > 0));
+        Self { reactants, products }
+    }
+    // 組成式から係数を決める
+    pub fn balance(reactants: Vec<Substance>, products: Vec<Substance>) -> Result<Self, ReactionError> {
+        let coefficients = balance_coefficients(&reactants, &products, false)?;
+        let (r, p) = coefficients.split_at(reactants.len());
+        Ok(Self::new(
+            reactants.into_iter().zip(r.iter().copied()).collect(),
+            products.into_iter().zip(p.iter().copied()).collect(),
+        ))
+    }
+    pub fn get_reactants(&self) -> &[(Substance, u32)] {
+        &self.reactants
+    }
+    pub fn get_products(&self) -> &[(Substance, u32)] {
+        &self.products
+    }
+    // 溶液に溶けているものを反応物とする
+    // 反応物は溶質の名前で探すので, KMnO4の溶液ならMnO4⁻ではなくKMnO4で反応式を書く
+    pub fn run_in(&self, solution: &Solution) -> Result<ReactionYield, ReactionError> {
+        self.run(&solution.get_substances()).map_err(|e| only_as_ion(solution, e))
+    }
+    pub fn run_with(&self, solids: &[Solid]) -> Result<ReactionYield, ReactionError> {
+        let amounts: Vec<(Substance, Mol)> = solids
+            .iter()
+            .map(|s| (s.get_substance().clone(), s.get_mol()))
+            .collect();
+        self.run(&amounts)
+    }
+    // 反応進行度 ξ = n / ν が最小の反応物が限定試薬
+    fn run(&self, amounts: &[(Substance, Mol)]) -> Result<ReactionYield, ReactionError> {
+        let mut extents = vec![];
+        for (substance, nu) in self.reactants.iter().filter(|(s, _)| !is_solvent(s)) {
+            let n = amount_of(amounts, substance)
+                .ok_or_else(|| ReactionError::MissingReactant(substance.get_name().to_string()))?;
+            check_amount(substance, n)?;
+            extents.push((substance, n, n / exact(*nu)));
+        }
+        let (limiting, _, extent) = extents
+            .iter()
+            .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal))
+            .cloned()
+            .ok_or(ReactionError::NoReactant)?;
+        let excess = extents
+            .iter()
+            .filter(|(s, _, _)| s.get_name() != limiting.get_name())
+            .map(|(s, n, _)| {
+                let nu = self.coefficient_of(s);
+                let left = (*n - extent * exact(nu)).into_same_unit_with(&Mol::mol_from(0.0));
+                (s.get_name().to_string(), left)
+            })
+            .collect();
+        let products = self
+            .products
+            .iter()
+            .map(|(s, nu)| (s.clone(), (extent * exact(*nu)).into_same_unit_with(&Mol::mol_from(0.0))))
+            .collect();
+        Ok(ReactionYield {
+            limiting: limiting.get_name().to_string(),
+            extent,
+            excess,
+            products,
+        })
+    }
+    fn coefficient_of(&self, substance: &Substance) -> u32 {
+        self.reactants
+            .iter()
+            .chain(self.products.iter())
+            .find(|(s, _)| s.get_name() == substance.get_name())
+            .map(|(_, nu)| *nu)
+            .unwrap()
+    }
+}
+
+impl std::fmt::Display for Reaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let side = |terms: &[(Substance, u32)]| {
+            terms
+                .iter()
+                .map(|(s, nu)| match nu {
+                    1 => s.get_name().to_string(),
+                    _ => format!("{} {}", nu, s.get_name()),
+                })
+                .collect::<Vec<_>>()
+                .join(" + ")
+        };
+        write!(f, "{} → {}", side(&self.reactants), side(&self.products))
+    }
+}
+
+// 元素ごとの原子数と電荷の保存を行列にして, その零空間の最小の整数ベクトルを求める
+// electronなら左辺の最後に電子を置く (半反応式). 係数は左辺, 電子, 右辺の順
+pub(crate) fn balance_coefficients(
+    left: &[Substance],
+    right: &[Substance],
+    electron: bool,
+) -> Result<Vec<u32>, ReactionError> {
+    let formulas = left
+        .iter()
+        .chain(right.iter())
+        .map(|s| {
+            s.get_formula()
+                .ok_or_else(|| ReactionError::MissingFormula(s.get_name().to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let elements: BTreeSet<&str> = formulas
+        .iter()
+        .flat_map(|f| f.get_element_counts().into_keys())
+        .collect();
+    // (元素ごとの数, 電荷) を左辺は正, 右辺は負で並べる
+    let mut columns: Vec<(Vec<i128>, i128)> = formulas
+        .iter()
+        .enumerate()
+        .map(|(j, f)| {
+            let sign = if j < left.len() { 1 } else { -1 };
+            let counts = elements.iter().map(|e| sign * f.get_element_count(e) as i128).collect();
+            (counts, sign * f.get_charge() as i128)
+        })
+        .collect();
+    if electron {
+        columns.insert(left.len(), (vec![0; elements.len()], -1));
+    }
+    let mut matrix: Vec<Vec<i128>> = (0..elements.len())
+        .map(|i| columns.iter().map(|(counts, _)| counts[i]).collect())
+        .collect();
+    matrix.push(columns.iter().map(|(_, charge)| *charge).collect());
+    integer_null_vector(matrix, columns.len())
+}
+
+// 溶媒の水は十分あるとして物質量を数えない
+pub(crate) fn is_solvent(substance: &Substance) -> bool {
+    substance.get_formula().is_some_and(|f| {
+        f.get_charge() == 0 && f.get_element_counts() == [("H", 2), ("O", 1)].into_iter().collect()
+    })
+}
+
+// どこまで反応させるか
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReactionExtent {
+    // 限定試薬がなくなるまで
+    Complete,
+    // 濃度平衡定数 K = Π[生成物]^ν / Π[反応物]^ν (水は含めない) になるまで. 逆向きにも進む
+    Equilibrium(f64),
+}
+
+// 溶液の中で反応させた結果
+#[derive(Debug, Clone)]
+pub struct ReactionRecord {
+    reaction: Reaction,
+    extent: Mol,
+    // 物質量の変化 (減ったものは負)
+    changes: Vec<(String, Mol)>,
+}
+
+impl ReactionRecord {
+    pub fn get_reaction(&self) -> &Reaction {
+        &self.reaction
+    }
+    pub fn get_extent(&self) -> Mol {
+        self.extent
+    }
+    pub fn get_changes(&self) -> &[(String, Mol)] {
+        &self.changes
+    }
+    pub fn get_change(&self, name: &str) -> Option<Mol> {
+        self.changes.iter().find(|(n, _)| n == name).map(|(_, mol)| *mol)
+    }
+}
+
+impl std::fmt::Display for ReactionRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.reaction)?;
+        for (name, mol) in self.changes.iter() {
+            let mol = mol.into_same_unit_with(&Mol::milli_mol_from(0.0));
+            write!(f, "\n  {}: {} mmol", name, mol.get_digit())?;
+        }
+        Ok(())
+    }
+}
+
+// 同じ名前のものは足しあわせる
+fn amount_of(amounts: &[(Substance, Mol)], substance: &Substance) -> Option<Mol> {
+    amounts
+        .iter()
+        .filter(|(s, _)| s.get_name() == substance.get_name())
+        .map(|(_, n)| *n)
+        .reduce(|a, b| a + b)
+}
+
+// 溶質にはないが塩のイオンとしてはあるときは, そう知らせる
+fn only_as_ion(solution: &Solution, error: ReactionError) -> ReactionError {
+    match error {
+        ReactionError::MissingReactant(name) if solution.get_ion_concentration().contains_key(&name) => {
+            ReactionError::OnlyAsIon(name)
+        }
+        e => e,
+    }
+}
+
+// 溶液の中身を書きかえる
+// 溶質 (塩) の単位で書きかえるので, 反応物はrun_inと同じく溶質の名前で探す
+// 進行度の有効数字と不確かさは, 進める向きにいちばん早くなくなる物質から引き継ぐ
+pub(crate) fn react(
+    solution: &mut Solution,
+    reaction: &Reaction,
+    extent: ReactionExtent,
+) -> Result<ReactionRecord, ReactionError> {
+    let amounts = solution.get_substances();
+    for (s, n) in amounts.iter() {
+        check_amount(s, *n)?;
+    }
+    let amount_of = |substance: &Substance| amount_of(&amounts, substance);
+    let terms: Vec<(&Substance, i32)> = reaction
+        .reactants
+        .iter()
+        .map(|(s, nu)| (s, -(*nu as i32)))
+        .chain(reaction.products.iter().map(|(s, nu)| (s, *nu as i32)))
+        .filter(|(s, _)| !is_solvent(s))
+        .collect();
+    // ξ を増やしたとき (減らしたとき) にいちばん早くなくなる物質
+    let bound = |direction: i32| {
+        terms
+            .iter()
+            .filter(|(_, nu)| nu.signum() != direction)
+            .map(|(s, nu)| {
+                let n = amount_of(s).unwrap_or(0.0.into());
+                (n / DimSigDig::nodim_from(nu.abs()).set_uncertainty(0.0)).into_same_unit_with(&Mol::mol_from(0.0))
+            })
+            .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+    };
+
+    let xi = match extent {
+        ReactionExtent::Complete => {
+            for (s, _) in reaction.reactants.iter().filter(|(s, _)| !is_solvent(s)) {
+                amount_of(s).ok_or_else(|| {
+                    only_as_ion(solution, ReactionError::MissingReactant(s.get_name().to_string()))
+                })?;
+            }
+            bound(1).ok_or(ReactionError::NoReactant)?
+        }
+        ReactionExtent::Equilibrium(k) => {
+            assert!(k > 0.0);
+            let liter = solution
+                .get_volume()
+                .into_same_unit_with(&Volume::liter_from(0.0))
+                .get_raw_num();
+            let raw = |s: &Substance| {
+                amount_of(s)
+                    .map(|n| n.into_same_unit_with(&Mol::mol_from(0.0)).get_raw_num())
+                    .unwrap_or(0.0)
+            };
+            // ln Q はξについて単調に増える
+            let ln_q = |xi: f64| {
+                terms
+                    .iter()
+                    .map(|(s, nu)| *nu as f64 * ((raw(s) + *nu as f64 * xi) / liter).max(0.0).ln())
+                    .sum::<f64>()
+            };
+            let high = bound(1).map(|b| b.get_raw_num()).unwrap_or(0.0);
+            let low = -bound(-1).map(|b| b.get_raw_num()).unwrap_or(0.0);
+            let (mut l, mut h) = (low, high);
+            for _ in 0..200 {
+                let mid = (l + h) / 2.0;
+                if ln_q(mid) < k.ln() {
+                    l = mid;
+                } else {
+                    h = mid;
+                }
+            }
+            let xi = (l + h) / 2.0;
+            let reference = match xi >= 0.0 {
+                true => bound(1),
+                false => bound(-1),
+            };
+            match reference {
+                Some(reference) if reference.get_raw_num() > 0.0 => {
+                    reference * DimSigDig::nodim_from(xi / reference.get_raw_num()).set_uncertainty(0.0)
+                }
+                _ => Mol::mol_from(0.0),
+            }
+        }
+    };
+
+    // 途中で失敗して中身が半端に書きかわらないよう先に確かめる
+    for (s, _) in &terms {
+        solution.check_same_substance(s)?;
+    }
+    let mut changes = vec![];
+    for (s, nu) in terms {
+        let delta = xi * DimSigDig::nodim_from(nu).set_uncertainty(0.0);
+        solution.change_mol(s, delta)?;
+        changes.push((s.get_name().to_string(), delta));
+    }
+    Ok(ReactionRecord {
+        reaction: reaction.clone(),
+        extent: xi,
+        changes,
+    })
+}
+
+fn check_amount(substance: &Substance, n: Mol) -> Result<(), ReactionError> {
+    match n.get_raw_num().is_finite() {
+        true => Ok(()),
+        false => Err(ReactionError::InvalidAmount(substance.get_name().to_string())),
+    }
+}
+
+// 係数は数えた数なので不確かさはない
+fn exact(nu: u32) -> NoDim {
+    DimSigDig::nodim_from(nu).set_uncertainty(0.0)
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// 整数のまま行を簡約して, 1次元の零空間を張る正の整数ベクトルを返す
+fn integer_null_vector(mut matrix: Vec<Vec<i128>>, n: usize) -> Result<Vec<u32>, ReactionError> {
+    let mut pivots = vec![];
+    let mut row = 0;
+    for col in 0..n {
+        let Some(i) = (row..matrix.len()).find(|i| matrix[*i][col] != 0) else {
+            continue;
+        };
+        matrix.swap(row, i);
+        for k in 0..matrix.len() {
+            if k == row || matrix[k][col] == 0 {
+                continue;
+            }
+            let (a, b) = (matrix[row][col], matrix[k][col]);
+            let reduced: Vec<i128> = (0..n).map(|j| matrix[k][j] * a - matrix[row][j] * b).collect();
+            let g = reduced.iter().fold(0, |g, x| gcd(g, *x)).max(1);
+            matrix[k] = reduced.into_iter().map(|x| x / g).collect();
+        }
+        pivots.push((row, col));
+        row += 1;
+    }
+    let free: Vec<usize> = (0..n).filter(|j| pivots.iter().all(|(_, c)| c != j)).collect();
+    let free = match free.as_slice() {
+        [] => return Err(ReactionError::Unbalanceable),
+        [free] => *free,
+        _ => return Err(ReactionError::Ambiguous),
+    };
+    // 自由変数を各ピボットの最小公倍数にとれば全部整数になる
+    let scale = pivots
+        .iter()
+        .fold(1, |l, (r, c)| l / gcd(l, matrix[*r][*c]) * matrix[*r][*c].abs());
+    let mut x = vec![0; n];
+    x[free] = scale;
+    for (r, c) in pivots.iter() {
+        x[*c] = -matrix[*r][free] * scale / matrix[*r][*c];
+    }
+    let g = x.iter().fold(0, |g, v| gcd(g, *v));
+    let sign = if x[0] < 0 { -1 } else { 1 };
+    x.into_iter()
+        .map(|v| sign * v / g)
+        .map(|v| u32::try_from(v).ok().filter(|v| *v > 0))
+        .collect::<Option<Vec<u32>>>()
+        .ok_or(ReactionError::Unbalanceable)
+}
+
+// 反応が限定試薬の分だけ進んだときの量
+#[derive(Debug, Clone)]
+pub struct ReactionYield {
+    limiting: String,
+    // 反応進行度
+    extent: Mol,
+    // 限定試薬以外の反応物の残り
+    excess: Vec<(String, Mol)>,
+    products: Vec<(Substance, Mol)>,
+}
+
+impl ReactionYield {
+    pub fn get_limiting_reagent(&self) -> &str {
+        &self.limiting
+    }
+    pub fn get_extent(&self) -> Mol {
+        self.extent
+    }
+    pub fn get_excess(&self) -> &[(String, Mol)] {
+        &self.excess
+    }
+    pub fn get_theoretical_mol(&self, name: &str) -> Mol {
+        self.product(name).1
+    }
+    pub fn get_theoretical_mass(&self, name: &str) -> Mass {
+        let (substance, mol) = self.product(name);
+        (*mol * substance.get_molar_mass()).into_same_unit_with(&Mass::gram_from(0.0))
+    }
+    // 単離した質量から収率 (%)
+    pub fn get_percent_yield(&self, name: &str, isolated: Mass) -> NoDim {
+        let percent = DimSigDig::nodim_from(100.0).set_uncertainty(0.0);
+        (isolated / self.get_theoretical_mass(name) * percent)
+            .into_same_unit_with(&DimSigDig::nodim_from(0.0))
+    }
+    fn product(&self, name: &str) -> &(Substance, Mol) {
+        self.products
+            .iter()
+            .find(|(s, _)| s.get_name() == name)
+            .unwrap_or_else(|| panic!("{} is not a product", name))
+    }
+}
+
+#[test]
+fn balance_test() {
+    let substance = |formula: &str| Substance::from_formula(formula.to_string(), formula).unwrap();
+    let reaction = Reaction::balance(
+        vec![substance("C3H8"), substance("O2")],
+        vec![substance("CO2"), substance("H2O")],
+    )
+    .unwrap();
+    assert_eq!(format!("{}", reaction), "C3H8 + 5 O2 → 3 CO2 + 4 H2O");
+
+    // 電荷も保存する
+    let reaction = Reaction::balance(
+        vec![substance("MnO4-"), substance("Fe+2"), substance("H+")],
+        vec![substance("Mn+2"), substance("Fe+3"), substance("H2O")],
+    )
+    .unwrap();
+    assert_eq!(format!("{}", reaction), "MnO4- + 5 Fe+2 + 8 H+ → Mn+2 + 5 Fe+3 + 4 H2O");
+
+    let error = Reaction::balance(vec![substance("H2")], vec![substance("O2")]);
+    assert_eq!(error.unwrap_err(), ReactionError::Unbalanceable);
+    let error = Reaction::balance(
+        vec![substance("H2"), substance("O2")],
+        vec![substance("H2O"), substance("H2O2")],
+    );
+    assert_eq!(error.unwrap_err(), ReactionError::Ambiguous);
+}
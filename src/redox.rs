@@ -0,0 +1,207 @@
+use crate::acid_base::Equilibrium;
+use crate::dim_sig_dig::*;
+use crate::reaction::{balance_coefficients, is_solvent, ReactionError};
+use crate::substance::*;
+use crate::temperature::Temperature;
+
+// 気体定数 (J mol⁻¹ K⁻¹) とファラデー定数 (C mol⁻¹). どちらも定義値
+const GAS_CONSTANT: &str = "8.314462618 kg m^2 s^-2 mol^-1 K^-1";
+const FARADAY_CONSTANT: &str = "96485.33212 A s mol^-1";
+
+type GasConstant = DimSigDig<-1, 1, 2, -2, -1, 0, 0>;
+type FaradayConstant = DimSigDig<-1, 0, 0, 1, 0, 1, 0>;
+
+// RT/F
+fn thermal_voltage(temperature: Temperature) -> Voltage {
+    let r: GasConstant = GAS_CONSTANT.parse().unwrap();
+    let f: FaradayConstant = FARADAY_CONSTANT.parse().unwrap();
+    (r.set_uncertainty(0.0) * temperature.above_absolute_zero() / f.set_uncertainty(0.0))
+        .into_same_unit_with(&Voltage::volt_from(0.0))
+}
+
+fn standard_temperature() -> Temperature {
+    Temperature::celsius_from(25.0).set_uncertainty(0.0)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RedoxError {
+    // 溶質にもイオンにもない (滴定の最初の一滴の前の生成物など)
+    NotInSolution(String),
+    // 濃度が0なのでNernst式の対数が発散する
+    ZeroConcentration(String),
+}
+
+impl std::fmt::Display for RedoxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotInSolution(name) => write!(f, "{} is not in the solution", name),
+            Self::ZeroConcentration(name) => write!(f, "the concentration of {} is zero", name),
+        }
+    }
+}
+
+impl std::error::Error for RedoxError {}
+
+// 還元半反応 Ox + ne⁻ → Red と標準電極電位
+// MnO4⁻ + 8H⁺ + 5e⁻ → Mn²⁺ + 4H2O (1.507 V) のようにH⁺や水も書く
+#[derive(Debug, Clone)]
+pub struct HalfReaction {
+    oxidized: Vec<(Substance, u32)>,
+    electrons: u32,
+    reduced: Vec<(Substance, u32)>,
+    standard_potential: Voltage,
+}
+
+impl HalfReaction {
+    pub fn new(
+        oxidized: Vec<(Substance, u32)>,
+        electrons: u32,
+        reduced: Vec<(Substance, u32)>,
+        standard_potential: Voltage,
+    ) -> Self {
+        assert!(electrons > 0);
+        assert!(oxidized.iter().chain(reduced.iter()).all(|(_, n)| *n > 0));
+        Self {
+            oxidized,
+            electrons,
+            reduced,
+            standard_potential,
+        }
+    }
+    // 電子の数も組成式と電荷から決める
+    pub fn balance(
+        oxidized: Vec<Substance>,
+        reduced: Vec<Substance>,
+        standard_potential: Voltage,
+    ) -> Result<Self, ReactionError> {
+        let coefficients = balance_coefficients(&oxidized, &reduced, true)?;
+        let (ox, rest) = coefficients.split_at(oxidized.len());
+        let (electrons, red) = rest.split_first().unwrap();
+        Ok(Self::new(
+            oxidized.into_iter().zip(ox.iter().copied()).collect(),
+            *electrons,
+            reduced.into_iter().zip(red.iter().copied()).collect(),
+            standard_potential,
+        ))
+    }
+    pub fn get_oxidized(&self) -> &[(Substance, u32)] {
+        &self.oxidized
+    }
+    pub fn get_reduced(&self) -> &[(Substance, u32)] {
+        &self.reduced
+    }
+    pub fn get_electrons(&self) -> u32 {
+        self.electrons
+    }
+    pub fn get_standard_potential(&self) -> Voltage {
+        self.standard_potential
+    }
+    // 酸化体側のH⁺の数 (還元体側にあれば負)
+    fn get_protons(&self) -> i32 {
+        let count = |side: &[(Substance, u32)]| {
+            side.iter()
+                .filter(|(s, _)| is_proton(s))
+                .map(|(_, n)| *n as i32)
+                .sum::<i32>()
+        };
+        count(&self.oxidized) - count(&self.reduced)
+    }
+    pub fn get_potential(&self, solution: &Solution) -> Result<Voltage, RedoxError> {
+        self.get_potential_at(solution, standard_temperature())
+    }
+    // Nernst式 E = E° - (RT/nF) ln(Π[Red]^ν / Π[Ox]^ν)
+    // 濃度は1 mol/L基準で活量係数は1とみなす. 水は数えない
+    // 酸化体か還元体のどちらかがないと電位は決まらないのでエラー
+    pub fn get_potential_at(&self, solution: &Solution, temperature: Temperature) -> Result<Voltage, RedoxError> {
+        let mut ln_q = 0.0;
+        let mut variance = 0.0;
+        for (side, sign) in [(&self.reduced, 1.0), (&self.oxidized, -1.0)] {
+            for (substance, nu) in side.iter().filter(|(s, _)| !is_solvent(s)) {
+                let c = concentration_in(solution, substance)?;
+                let (value, u) = (c.get_molar_num(), c.get_uncertainty().get_molar_num());
+                if value <= 0.0 {
+                    return Err(RedoxError::ZeroConcentration(substance.get_name().to_string()));
+                }
+                ln_q += sign * *nu as f64 * value.ln();
+                variance += (*nu as f64 * u / value).powi(2);
+            }
+        }
+        let n = self.electrons as f64;
+        let log_term = DimSigDig::nodim_from(ln_q / n).set_uncertainty(variance.sqrt() / n);
+        Ok(self.standard_potential - thermal_voltage(temperature) * log_term)
+    }
+}
+
+impl std::fmt::Display for HalfReaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let side = |terms: &[(Substance, u32)]| {
+            terms
+                .iter()
+                .map(|(s, nu)| match nu {
+                    1 => s.get_name().to_string(),
+                    _ => format!("{} {}", nu, s.get_name()),
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut left = side(&self.oxidized);
+        left.push(match self.electrons {
+            1 => "e-".to_string(),
+            n => format!("{} e-", n),
+        });
+        write!(
+            f,
+            "{} → {} (E° = {} V)",
+            left.join(" + "),
+            side(&self.reduced).join(" + "),
+            self.standard_potential.into_same_unit_with(&Voltage::volt_from(0.0)).get_digit()
+        )
+    }
+}
+
+fn is_proton(substance: &Substance) -> bool {
+    substance.get_formula().is_some_and(|f| {
+        f.get_charge() == 1 && f.get_element_counts() == [("H", 1)].into_iter().collect()
+    })
+}
+
+// 溶質の名前, イオンの名前の順に探す. H⁺が溶質になければpHから求める
+fn concentration_in(solution: &Solution, substance: &Substance) -> Result<Concentration, RedoxError> {
+    let name = substance.get_name();
+    if let Some(c) = solution.get_concentration().get(name) {
+        return Ok(*c);
+    }
+    if let Some(c) = solution.get_ion_concentration().get(name) {
+        return Ok(*c);
+    }
+    if is_proton(substance) {
+        return Ok(Equilibrium::of(solution).get_h_concentration());
+    }
+    Err(RedoxError::NotInSolution(name.to_string()))
+}
+
+// 滴定剤の酸化体で試料の還元体を滴定するときの温度temperatureでの当量点の電位
+// E = (n1 E1° + n2 E2°) / (n1 + n2) - (RT ln10 / F) m pH / (n1 + n2), mは酸化体側のH⁺
+// 二クロム酸 (Cr2O7²⁻ → 2Cr³⁺) のように係数が非対称な反応では [Cr³⁺] の項を無視した近似になる
+pub fn equivalence_potential(
+    titrant: &HalfReaction,
+    analyte: &HalfReaction,
+    ph: NoDim,
+    temperature: Temperature,
+) -> Voltage {
+    let exact = |x: f64| DimSigDig::nodim_from(x).set_uncertainty(0.0);
+    let (n1, n2) = (titrant.electrons as f64, analyte.electrons as f64);
+    let m = (titrant.get_protons() + analyte.get_protons()) as f64;
+    let mean = (titrant.standard_potential * exact(n1) + analyte.standard_potential * exact(n2))
+        * exact(1.0 / (n1 + n2));
+    let slope = thermal_voltage(temperature) * exact(10_f64.ln() * m / (n1 + n2));
+    (mean - slope * ph).into_same_unit_with(&Voltage::volt_from(0.0))
+}
+
+#[test]
+fn thermal_voltage_test() {
+    // RT ln10 / F = 59.16 mV (25 ℃)
+    let slope = thermal_voltage(standard_temperature()) * DimSigDig::nodim_from(10_f64.ln());
+    assert!((slope.get_volt_num() - 0.05916).abs() < 1e-5);
+    let at_37 = thermal_voltage(Temperature::kelvin_from(310.15));
+    assert!((at_37.get_volt_num() / thermal_voltage(standard_temperature()).get_volt_num() - 310.15 / 298.15).abs() < 1e-12);
+}
@@ -0,0 +1,176 @@
+use crate::dim_sig_dig::*;
+use crate::substance::*;
+
+use std::collections::HashMap;
+
+// 溶解度積を超えて沈殿する塩
+#[derive(Debug, Clone)]
+pub struct Precipitate {
+    substance: Substance,
+    // 混ぜた直後のイオン積 / Ksp
+    saturation: f64,
+    amount: Mol,
+    // 沈殿したあとに溶けて残るイオンの濃度
+    dissolved: HashMap<String, Concentration>,
+}
+
+impl Precipitate {
+    pub fn get_substance(&self) -> &Substance {
+        &self.substance
+    }
+    pub fn get_saturation(&self) -> f64 {
+        self.saturation
+    }
+    pub fn get_amount(&self) -> Mol {
+        self.amount
+    }
+    pub fn get_dissolved(&self) -> &HashMap<String, Concentration> {
+        &self.dissolved
+    }
+}
+
+impl std::fmt::Display for Precipitate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} precipitates (Q/Ksp = {:.1e}): {} mmol",
+            self.substance.get_name(),
+            self.saturation,
+            self.amount.into_same_unit_with(&Mol::milli_mol_from(0.0)).get_digit()
+        )
+    }
+}
+
+// 溶媒が少なすぎて溶けきらない固体
+#[derive(Debug, Clone)]
+pub struct Undissolved {
+    name: String,
+    concentration: MassConcentration,
+    solubility: MassConcentration,
+}
+
+impl Undissolved {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn get_concentration(&self) -> MassConcentration {
+        self.concentration
+    }
+    pub fn get_solubility(&self) -> MassConcentration {
+        self.solubility
+    }
+}
+
+impl std::fmt::Display for Undissolved {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} does not dissolve: {} exceeds the solubility {}",
+            self.name, self.concentration, self.solubility
+        )
+    }
+}
+
+pub(crate) fn check_solubility(solution: &Solution) -> Vec<Undissolved> {
+    solution
+        .get_substances()
+        .into_iter()
+        .filter_map(|(substance, _)| {
+            let solubility = substance.get_solubility()?;
+            let concentration = solution.get_mass_by_name(substance.get_name()) / solution.get_volume();
+            let concentration = concentration.into_same_unit_with(&solubility);
+            (concentration > solubility).then(|| Undissolved {
+                name: substance.get_name().to_string(),
+                concentration,
+                solubility,
+            })
+        })
+        .collect()
+}
+
+// イオン積とKspを濃度で比べる (活量係数は1とみなす)
+// 過飽和の大きい塩から順に沈殿させ, 残ったイオンで次の塩を調べる
+// Kspかイオンのない塩は沈殿しないものとして飛ばす
+pub(crate) fn check_precipitation(solution: &Solution, salts: &[Substance]) -> Vec<Precipitate> {
+    let sig_dig = solution
+        .get_ion_concentration()
+        .values()
+        .map(|c| c.get_digit().get_sig_dig())
+        .min()
+        .unwrap_or(3);
+    let mut ions: HashMap<String, f64> = solution
+        .get_ion_concentration()
+        .into_iter()
+        .map(|(name, c)| (name, c.get_molar_num()))
+        .collect();
+    let liter = solution
+        .get_volume()
+        .into_same_unit_with(&Volume::liter_from(0.0))
+        .get_raw_num();
+    let ion_product = |ions: &HashMap<String, f64>, salt: &Substance, s: f64| {
+        salt.get_ions()
+            .iter()
+            .map(|ion| {
+                let c = ions.get(ion.get_name()).cloned().unwrap_or(0.0);
+                let count = ion.get_count() as f64;
+                count * (c - count * s).max(0.0).ln()
+            })
+            .sum::<f64>()
+    };
+    let ln_ksp = |salt: &Substance| salt.get_ksp().unwrap().ln();
+
+    let mut result = vec![];
+    let mut remaining: Vec<&Substance> = salts
+        .iter()
+        .filter(|salt| salt.get_ksp().is_some() && !salt.get_ions().is_empty())
+        .collect();
+    loop {
+        // いちばん過飽和なもの
+        let next = remaining
+            .iter()
+            .enumerate()
+            .map(|(i, salt)| (i, ion_product(&ions, salt, 0.0) - ln_ksp(salt)))
+            .filter(|(_, excess)| *excess > 0.0)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        let Some((i, excess)) = next else {
+            break;
+        };
+        let salt = remaining.remove(i);
+        // Π (c - ν s)^ν = Ksp をsについて二分法で解く
+        let (mut low, mut high) = (
+            0.0,
+            salt.get_ions()
+                .iter()
+                .map(|ion| ions[ion.get_name()] / ion.get_count() as f64)
+                .fold(f64::MAX, f64::min),
+        );
+        for _ in 0..100 {
+            let mid = (low + high) / 2.0;
+            if ion_product(&ions, salt, mid) > ln_ksp(salt) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        let s = (low + high) / 2.0;
+        for ion in salt.get_ions() {
+            let c = ions.get_mut(ion.get_name()).unwrap();
+            *c = (*c - ion.get_count() as f64 * s).max(0.0);
+        }
+        let dissolved = salt
+            .get_ions()
+            .iter()
+            .map(|ion| {
+                let c = Concentration::molar_from(ions[ion.get_name()]).set_sig_dig(sig_dig);
+                (ion.get_name().to_string(), c)
+            })
+            .collect();
+        result.push(Precipitate {
+            substance: salt.clone(),
+            saturation: excess.exp(),
+            amount: Mol::mol_from(s * liter).set_sig_dig(sig_dig),
+            dissolved,
+        });
+    }
+    result
+}
@@ -1,15 +1,140 @@
+use crate::acid_base::{AcidBase, Equilibrium};
+use crate::activity::*;
+use crate::reaction::{self, Reaction, ReactionError, ReactionExtent, ReactionRecord};
 use crate::dim_sig_dig::*;
+use crate::formula::*;
+use crate::monte_carlo::Sampler;
+use crate::solubility::{self, Precipitate, Undissolved};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
 pub struct Substance {
     name: String,
     molar_mass: MolarMass,
+    formula: Option<Formula>,
+    cas: Option<String>,
+    // 純度 (質量分率)
+    purity: NoDim,
+    // 酸・塩基としての性質 (なければ中性)
+    acid_base: Option<AcidBase>,
+    // 電離して生じるイオン (なければ非電解質)
+    ions: Vec<Ion>,
+    // 溶解度積 (mol/L基準). ionsから沈殿する塩に持たせる
+    ksp: Option<f64>,
+    // 水への溶解度
+    solubility: Option<MassConcentration>,
 }
 
 impl Substance {
     pub fn create(name: String, molar_mass: MolarMass) -> Self {
-        Self {name, molar_mass}
+        Self {
+            name,
+            molar_mass,
+            formula: None,
+            cas: None,
+            purity: DimSigDig::nodim_from(1.0).set_uncertainty(0.0),
+            acid_base: None,
+            ions: vec![],
+            ksp: None,
+            solubility: None,
+        }
+    }
+    // モル質量は原子量表から求める
+    pub fn from_formula(name: String, formula: &str) -> Result<Self, FormulaError> {
+        let formula: Formula = formula.parse()?;
+        Ok(Self {
+            formula: Some(formula.clone()),
+            ..Self::create(name, formula.get_molar_mass())
+        })
+    }
+    // 99.5 % のような表示値 (不確かさを持たせるならset_uncertaintyしておく)
+    pub fn set_purity(self, purity: NoDim) -> Self {
+        Self { purity, ..self }
+    }
+    // "assay 98.0–102.0 %" のような範囲は一様分布とみなす
+    pub fn set_assay_range(self, low: NoDim, high: NoDim) -> Self {
+        let high = high.into_same_unit_with(&low);
+        assert!(low <= high);
+        let width = high.get_raw_num() - low.get_raw_num();
+        let purity = ((low + high) * DimSigDig::nodim_from(0.5))
+            .set_uncertainty(width / 2.0 / 3_f64.sqrt());
+        self.set_purity(purity)
+    }
+    pub fn get_purity(&self) -> NoDim {
+        self.purity
+    }
+    pub fn set_acid_base(self, acid_base: AcidBase) -> Self {
+        Self {
+            acid_base: Some(acid_base),
+            ..self
+        }
+    }
+    pub fn get_acid_base(&self) -> Option<&AcidBase> {
+        self.acid_base.as_ref()
+    }
+    // NaClなら Na⁺ と Cl⁻ (完全に電離するとみなす)
+    pub fn set_ions(self, ions: Vec<Ion>) -> Self {
+        Self { ions, ..self }
+    }
+    pub fn get_ions(&self) -> &[Ion] {
+        &self.ions
+    }
+    pub fn set_ksp(self, ksp: f64) -> Self {
+        assert!(ksp > 0.0);
+        Self {
+            ksp: Some(ksp),
+            ..self
+        }
+    }
+    pub fn get_ksp(&self) -> Option<f64> {
+        self.ksp
+    }
+    pub fn set_solubility(self, solubility: MassConcentration) -> Self {
+        Self {
+            solubility: Some(solubility),
+            ..self
+        }
+    }
+    pub fn get_solubility(&self) -> Option<MassConcentration> {
+        self.solubility
+    }
+    pub fn set_cas(self, cas: String) -> Self {
+        Self {
+            cas: Some(cas),
+            ..self
+        }
+    }
+    pub fn get_cas(&self) -> Option<&str> {
+        self.cas.as_deref()
+    }
+    // 名前が同じでも中身が違えば別物. 純度は試薬ごとに違うので見ない
+    pub fn is_same_substance(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.cas == other.cas
+            && self.formula == other.formula
+            && self.molar_mass.into_same_unit_with(&other.molar_mass).get_raw_num()
+                == other.molar_mass.get_raw_num()
+    }
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn get_molar_mass(&self) -> MolarMass {
+        self.molar_mass
+    }
+    pub fn get_formula(&self) -> Option<&Formula> {
+        self.formula.as_ref()
+    }
+    fn expect_formula(&self) -> &Formula {
+        self.formula
+            .as_ref()
+            .unwrap_or_else(|| panic!("{} has no formula", self.name))
+    }
+    // 1 molあたりの元素のmol数
+    pub fn get_element_count(&self, symbol: &str) -> NoDim {
+        DimSigDig::nodim_from(self.expect_formula().get_element_count(symbol))
+    }
+    pub fn get_mass_fraction(&self, symbol: &str) -> NoDim {
+        self.expect_formula().get_mass_fraction(symbol)
     }
 }
 
@@ -18,25 +143,49 @@ pub struct Solid {
     substance: Substance,
     mass: Mass,
     volume: Volume,
+    // 混ぜると質量で平均した値になる
+    purity: NoDim,
 }
 
 impl Solid {
     pub fn create(substance: Substance, mass: Mass, volume: Volume) -> Self {
-        Self {substance, mass, volume}
+        let purity = substance.purity;
+        Self {substance, mass, volume, purity}
     }
     pub fn get_name(&self) -> String {
         self.substance.name.clone()
     }
+    pub fn get_substance(&self) -> &Substance {
+        &self.substance
+    }
     fn get_mass(&self) -> Mass {
         self.mass
     }
-    fn get_mol(&self) -> Mol {
-        self.mass / self.substance.molar_mass
+    // 純度の分だけ減らす
+    pub(crate) fn get_mol(&self) -> Mol {
+        self.mass * self.purity / self.substance.molar_mass
+    }
+    pub fn get_mol_of_element(&self, symbol: &str) -> Mol {
+        self.get_mol() * self.substance.get_element_count(symbol)
+    }
+    // get_molと同じく純度の分だけ減らす
+    pub fn get_mass_of_element(&self, symbol: &str) -> Mass {
+        self.mass * self.purity * self.substance.get_mass_fraction(symbol)
     }
     fn add_same_substance(&mut self, s: Solid) {
-        assert_eq!(self.substance.name, s.substance.name);
-        assert_eq!(self.substance.molar_mass, self.substance.molar_mass);
-        self.mass += s.mass;
+        assert!(
+            self.substance.is_same_substance(&s.substance),
+            "{:?} and {:?} have the same name but are different substances",
+            self.substance,
+            s.substance,
+        );
+        let mass = self.mass + s.mass;
+        if self.mass.is_zero() {
+            self.purity = s.purity;
+        } else if !s.mass.is_zero() && !mass.is_zero() {
+            self.purity = (self.mass * self.purity + s.mass * s.purity) / mass;
+        }
+        self.mass = mass;
         self.volume += s.volume;
     }
     fn zero(&self) -> Self {
@@ -48,10 +197,55 @@ impl Solid {
     }
 }
 
+// 濃塩酸 (37 %, 1.19 g/mL) のように体積ではかりとる液体試薬
+#[derive(Debug, Clone)]
+pub struct StockReagent {
+    substance: Substance,
+    density: MassConcentration,
+    mass_fraction: NoDim,
+}
+
+impl StockReagent {
+    pub fn create(substance: Substance, density: MassConcentration, mass_fraction: NoDim) -> Self {
+        Self {substance, density, mass_fraction}
+    }
+    // モル濃度のわかっている保存溶液
+    pub fn from_concentration(substance: Substance, c: Concentration) -> Self {
+        let density = c * substance.molar_mass;
+        Self::create(substance, density, DimSigDig::nodim_from(1.0).set_uncertainty(0.0))
+    }
+    pub fn get_name(&self) -> String {
+        self.substance.name.clone()
+    }
+    pub fn get_substance(&self) -> &Substance {
+        &self.substance
+    }
+    pub fn get_concentration(&self) -> Concentration {
+        (self.density * self.mass_fraction / self.substance.molar_mass).convert_to_molar()
+    }
+    // vだけとり出した溶液 (溶媒も含めて体積はv)
+    pub fn dispense(&self, v: Volume) -> Solution {
+        let solid = Solid {
+            substance: self.substance.clone(),
+            mass: v * self.density * self.mass_fraction,
+            volume: 0.0.into(),
+            // 純度はmass_fractionに含めてある
+            purity: DimSigDig::nodim_from(1.0).set_uncertainty(0.0),
+        };
+        Solution {
+            solute: HashMap::from([(solid.get_name(), solid)]),
+            volume: v,
+            sampler: Sampler::default(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Solution {
     solute: HashMap<String, Solid>,
     volume: Volume,
+    // 秤量や器具の体積をばらつかせる (モンテカルロ法のときだけ)
+    sampler: Sampler,
 }
 
 impl Solution {
@@ -59,8 +253,15 @@ impl Solution {
         Self {
             solute: HashMap::new(),
             volume: 0.0.into(),
+            sampler: Sampler::default(),
         }
     }
+    pub fn set_sampler(self, sampler: Sampler) -> Self {
+        Self { sampler, ..self }
+    }
+    pub fn get_sampler(&self) -> &Sampler {
+        &self.sampler
+    }
     pub fn get_volume(&self) -> BasicDimSigDig<0, 0, 3> {
         self.volume
     }
@@ -70,6 +271,17 @@ impl Solution {
     pub fn get_mol_by_name(&self, name: &str) -> Mol {
         self.solute.get(name).map(|s| s.get_mol()).unwrap_or(0.0.into())
     }
+    // 酸・塩基の電荷収支から求めたpH
+    pub fn get_ph(&self) -> NoDim {
+        Equilibrium::of(self).get_ph()
+    }
+    // 溶けているものとその物質量
+    pub fn get_substances(&self) -> Vec<(Substance, Mol)> {
+        self.solute
+            .values()
+            .map(|s| (s.substance.clone(), s.get_mol()))
+            .collect()
+    }
     pub fn get_concentration(&self) -> HashMap<String, BasicDimSigDig<1, 0, -3>> {
         let mut result = HashMap::new();
         for name in self.solute.keys() {
@@ -78,7 +290,126 @@ impl Solution {
         }
         result
     }
-    pub fn add_substance(&mut self, s: Solid) {
+    // 同じイオンは化合物によらずまとめる
+    pub fn get_ion_concentration(&self) -> HashMap<String, Concentration> {
+        let mut result: HashMap<String, Concentration> = HashMap::new();
+        for s in self.solute.values() {
+            for ion in s.substance.get_ions() {
+                let count = DimSigDig::nodim_from(ion.get_count()).set_uncertainty(0.0);
+                let c = (s.get_mol() * count / self.volume).convert_to_molar();
+                result
+                    .entry(ion.get_name().to_string())
+                    .and_modify(|sum| *sum += c)
+                    .or_insert(c);
+            }
+        }
+        result
+    }
+    // I = 1/2 Σ c z²
+    pub fn get_ionic_strength(&self) -> Concentration {
+        let sum = self
+            .solute
+            .values()
+            .flat_map(|s| s.substance.get_ions().iter().map(move |ion| (s, ion)))
+            .map(|(s, ion)| {
+                let z2 = DimSigDig::nodim_from(ion.get_charge().pow(2) * ion.get_count() as i32)
+                    .set_uncertainty(0.0);
+                s.get_mol() * z2 / self.volume
+            })
+            .reduce(|a, b| a + b);
+        // イオンがなければ (空の溶液や非電解質だけなら) 0
+        let Some(sum) = sum else {
+            return Concentration::molar_from(0.0);
+        };
+        (sum * DimSigDig::nodim_from(0.5).set_uncertainty(0.0))
+            .convert_to_molar()
+            .normalized()
+    }
+    // イオンごとの活量 (無次元)
+    pub fn get_activity(&self, model: ActivityModel) -> HashMap<String, NoDim> {
+        let ionic_strength = self.get_ionic_strength();
+        let ions: HashMap<String, Ion> = self
+            .solute
+            .values()
+            .flat_map(|s| s.substance.get_ions().iter().cloned())
+            .map(|ion| (ion.get_name().to_string(), ion))
+            .collect();
+        self.get_ion_concentration()
+            .into_iter()
+            .map(|(name, c)| {
+                let gamma = model.get_coefficient(&ions[&name], ionic_strength);
+                (name, activity_of(c, gamma))
+            })
+            .collect()
+    }
+    // saltsのうちイオン積が溶解度積を超えるもの
+    pub fn check_precipitation(&self, salts: &[Substance]) -> Vec<Precipitate> {
+        solubility::check_precipitation(self, salts)
+    }
+    // 溶解度を超えて入っている固体
+    pub fn check_solubility(&self) -> Vec<Undissolved> {
+        solubility::check_solubility(self)
+    }
+    // 混ぜてから沈殿するものを調べる
+    pub fn mix(&mut self, s: Solution, salts: &[Substance]) -> Vec<Precipitate> {
+        self.add_solution(s);
+        self.check_precipitation(salts)
+    }
+    // 反応させて中身を書きかえる
+    pub fn react(&mut self, reaction: &Reaction, extent: ReactionExtent) -> Result<ReactionRecord, ReactionError> {
+        reaction::react(self, reaction, extent)
+    }
+    // 同名の溶質があれば同じ物質か確かめる
+    pub(crate) fn check_same_substance(&self, substance: &Substance) -> Result<(), ReactionError> {
+        match self.solute.get(substance.get_name()) {
+            Some(solid) if !solid.substance.is_same_substance(substance) => {
+                Err(ReactionError::DifferentSubstance(substance.get_name().to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+    // 物質量をdeltaだけ増やす (なくなったものは取り除く)
+    pub(crate) fn change_mol(&mut self, substance: &Substance, delta: Mol) -> Result<(), ReactionError> {
+        self.check_same_substance(substance)?;
+        let name = substance.get_name().to_string();
+        let Some(solid) = self.solute.get_mut(&name) else {
+            // 反応でできたものは純物質
+            let purity = DimSigDig::nodim_from(1.0).set_uncertainty(0.0);
+            let solid = Solid::create(substance.clone().set_purity(purity), delta * substance.molar_mass, 0.0.into());
+            self.solute.insert(name, solid);
+            return Ok(());
+        };
+        let before = solid.mass.get_raw_num().abs();
+        solid.mass += delta * substance.molar_mass / solid.purity;
+        if solid.mass.get_raw_num().abs() <= before * 1e-9 {
+            self.solute.remove(&name);
+        }
+        Ok(())
+    }
+    // 化合物によらず元素として数える. 組成式の無い溶質は0として数える
+    fn solute_with_formula(&self) -> impl Iterator<Item = &Solid> {
+        self.solute.values().filter(|s| s.substance.formula.is_some())
+    }
+    pub fn get_mol_of_element(&self, symbol: &str) -> Mol {
+        self.solute_with_formula()
+            .map(|s| s.get_mol_of_element(symbol))
+            .fold(0.0.into(), |a, b| a + b)
+    }
+    pub fn get_mass_of_element(&self, symbol: &str) -> Mass {
+        self.solute_with_formula()
+            .map(|s| s.get_mass_of_element(symbol))
+            .fold(0.0.into(), |a, b| a + b)
+    }
+    pub fn get_concentration_of_element(&self, symbol: &str) -> Concentration {
+        self.get_mol_of_element(symbol) / self.volume
+    }
+    // 希薄水溶液では mg/L ≒ ppm
+    pub fn get_mass_concentration_of_element(&self, symbol: &str) -> MassConcentration {
+        (self.get_mass_of_element(symbol) / self.volume).convert_to_milli_gram_per_liter()
+    }
+    pub fn add_substance(&mut self, mut s: Solid) {
+        // 秤量のばらつき
+        s.mass = self.sampler.sample(s.mass);
         // to be uncertain
         self.volume += s.volume;
         self.solute.entry(s.get_name())
@@ -87,6 +418,9 @@ impl Solution {
     }
     pub fn add_solution(&mut self, s: Solution) {
         self.volume += s.volume;
+        if self.sampler.is_exact() {
+            self.sampler = s.sampler;
+        }
 
         for (_, solute) in s.solute.into_iter() {
             self.solute.entry(solute.get_name())
@@ -99,6 +433,11 @@ impl Solution {
         self.volume = v;
     }
     pub fn dispense(&mut self, v: Volume) -> Solution {
+        // 全量を移すときは比を取らない (同じ量どうしの比に不確かさを持たせない)
+        if v.into_same_unit_with(&self.volume).get_raw_num() == self.volume.get_raw_num() {
+            let empty = Solution::new().set_sampler(self.sampler.clone());
+            return std::mem::replace(self, empty);
+        }
         let ratio = v / self.volume;
         let solution = {
             let mut solution = self.clone();
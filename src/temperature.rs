@@ -0,0 +1,127 @@
+use crate::dim_sig_dig::*;
+
+// 温度計の読みのような絶対的な温度. 中身は絶対零度からの差 (K)
+// 読みどうしの差は温度差になり, 読みどうしを足すことはできない
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Temperature(TemperatureDifference);
+
+// 0 ℃ = 273.15 K, 0 °F = 459.67 °R (どちらも定義値)
+fn exact(x: f64) -> TemperatureDifference {
+    TemperatureDifference::kelvin_from(x).set_uncertainty(0.0)
+}
+fn ice_point() -> TemperatureDifference {
+    exact(273.15)
+}
+fn rankine_per_kelvin() -> NoDim {
+    DimSigDig::nodim_from(1.8).set_uncertainty(0.0)
+}
+
+impl Temperature {
+    pub fn kelvin_from<U: Into<f64>>(t: U) -> Self {
+        Self::new(TemperatureDifference::kelvin_from(t))
+    }
+    pub fn celsius_from<U: Into<f64>>(t: U) -> Self {
+        Self::from_celsius(TemperatureDifference::kelvin_from(t))
+    }
+    pub fn fahrenheit_from<U: Into<f64>>(t: U) -> Self {
+        Self::from_fahrenheit(TemperatureDifference::kelvin_from(t))
+    }
+    // 絶対零度からの差
+    pub fn new(kelvin: TemperatureDifference) -> Self {
+        assert!(kelvin.get_kelvin_num() >= 0.0, "below absolute zero");
+        Self(kelvin)
+    }
+    // 0 ℃からの差で与える. 足すのは定義値なので小数点以下の桁はそのまま残る (25.0 ℃ -> 298.2 K)
+    pub fn from_celsius(celsius: TemperatureDifference) -> Self {
+        Self::new(celsius + ice_point())
+    }
+    pub fn from_fahrenheit(fahrenheit: TemperatureDifference) -> Self {
+        Self::new((fahrenheit + exact(459.67)) / rankine_per_kelvin())
+    }
+    pub fn set_uncertainty(self, uncertainty: f64) -> Self {
+        Self(self.0.set_uncertainty(uncertainty))
+    }
+    pub fn above_absolute_zero(&self) -> TemperatureDifference {
+        self.0
+    }
+    pub fn get_celsius(&self) -> TemperatureDifference {
+        self.0 - ice_point()
+    }
+    pub fn get_fahrenheit(&self) -> TemperatureDifference {
+        self.0 * rankine_per_kelvin() - exact(459.67)
+    }
+    pub fn get_kelvin_num(&self) -> f64 {
+        self.0.get_kelvin_num()
+    }
+    pub fn get_celsius_num(&self) -> f64 {
+        self.get_celsius().get_kelvin_num()
+    }
+    pub fn get_fahrenheit_num(&self) -> f64 {
+        self.get_fahrenheit().get_kelvin_num()
+    }
+}
+
+impl std::ops::Sub for Temperature {
+    type Output = TemperatureDifference;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.0 - rhs.0
+    }
+}
+
+impl std::ops::Add<TemperatureDifference> for Temperature {
+    type Output = Self;
+    fn add(self, rhs: TemperatureDifference) -> Self::Output {
+        Self::new(self.0 + rhs)
+    }
+}
+
+impl std::ops::Sub<TemperatureDifference> for Temperature {
+    type Output = Self;
+    fn sub(self, rhs: TemperatureDifference) -> Self::Output {
+        Self::new(self.0 - rhs)
+    }
+}
+
+// 実験室の温度計に合わせて℃で表示する
+impl std::fmt::Display for Temperature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} °C", self.get_celsius().get_digit())
+    }
+}
+
+// "25.0 °C", "77 °F", "298.15 K" のように書く
+impl std::str::FromStr for Temperature {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (number, unit) = s.split_at(s.find(|c: char| c.is_whitespace() || "°℃℉K".contains(c)).unwrap_or(s.len()));
+        let value: TemperatureDifference = format!("{} K", number).parse()?;
+        match unit.trim() {
+            "K" => Ok(Self::new(value)),
+            "°C" | "℃" | "degC" => Ok(Self::from_celsius(value)),
+            "°F" | "℉" | "degF" => Ok(Self::from_fahrenheit(value)),
+            unit => Err(ParseError::UnknownUnit(unit.to_string())),
+        }
+    }
+}
+
+#[test]
+fn temperature_test() {
+    let room: Temperature = "25.0 °C".parse().unwrap();
+    assert_eq!(format!("{}", room.above_absolute_zero().get_digit()), "298.2");
+    assert_eq!(format!("{}", room), "25.0 °C");
+    let body: Temperature = "98.6 °F".parse().unwrap();
+    assert!((body.get_celsius_num() - 37.0).abs() < 1e-9);
+    assert!((Temperature::kelvin_from(233.15).get_fahrenheit_num() + 40.0).abs() < 1e-9);
+    assert_eq!("310.2 K".parse::<Temperature>().unwrap().get_kelvin_num(), 310.2);
+    assert!("25 C".parse::<Temperature>().is_err());
+
+    // 読みどうしの差は温度差 (℃でもKでも同じ)
+    let warm: Temperature = "37.0 ℃".parse().unwrap();
+    let rise = warm - room;
+    assert!((rise.get_kelvin_num() - 12.0).abs() < 1e-9);
+    assert_eq!(format!("{}", rise.get_digit()), "12.0");
+    // 不確かさは伝播して大きくなるので値だけ比べる
+    assert!(((room + rise).get_kelvin_num() - warm.get_kelvin_num()).abs() < 1e-9);
+    assert!(((warm - TemperatureDifference::kelvin_from(12.0)).get_celsius_num() - 25.0).abs() < 1e-9);
+}
@@ -0,0 +1,75 @@
+use crate::dim_sig_dig::*;
+use crate::substance::*;
+
+// 滴定の量的関係: titrant_coefficient の滴定剤と analyte_coefficient の試料がちょうど反応する
+// 過マンガン酸カリウムとモール塩なら MnO4⁻ + 5Fe²⁺ なので 1 : 5
+#[derive(Debug, Clone)]
+pub struct Stoichiometry {
+    titrant: String,
+    titrant_coefficient: u32,
+    analyte: String,
+    analyte_coefficient: u32,
+}
+
+impl Stoichiometry {
+    pub fn new(titrant: &str, titrant_coefficient: u32, analyte: &str, analyte_coefficient: u32) -> Self {
+        assert!(titrant_coefficient > 0 && analyte_coefficient > 0);
+        Self {
+            titrant: titrant.to_string(),
+            titrant_coefficient,
+            analyte: analyte.to_string(),
+            analyte_coefficient,
+        }
+    }
+    pub fn get_titrant(&self) -> &str {
+        &self.titrant
+    }
+    pub fn get_analyte(&self) -> &str {
+        &self.analyte
+    }
+    // 係数は数えた数なので不確かさはない
+    fn analyte_per_titrant(&self) -> NoDim {
+        DimSigDig::nodim_from(self.analyte_coefficient as f64 / self.titrant_coefficient as f64)
+            .set_uncertainty(0.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Titration {
+    titrant_concentration: Concentration,
+    analyte_volume: Volume,
+    analyte_mol: Mol,
+    stoichiometry: Stoichiometry,
+}
+
+impl Titration {
+    // titrant: ビュレットに入れる溶液, analyte: 滴定される溶液 (ホールピペットでとったもの)
+    pub fn new(titrant: &Solution, analyte: &Solution, stoichiometry: Stoichiometry) -> Self {
+        let titrant_concentration = titrant
+            .get_concentration()
+            .get(stoichiometry.get_titrant())
+            .cloned()
+            .unwrap_or_else(|| panic!("{} is not in the titrant", stoichiometry.get_titrant()));
+        Self {
+            titrant_concentration,
+            analyte_volume: analyte.get_volume(),
+            analyte_mol: analyte.get_mol_by_name(stoichiometry.get_analyte()),
+            stoichiometry,
+        }
+    }
+    pub fn get_stoichiometry(&self) -> &Stoichiometry {
+        &self.stoichiometry
+    }
+    // 溶液のモデルから予想される当量点までの滴下量
+    pub fn get_equivalence_volume(&self) -> Volume {
+        (self.analyte_mol / self.stoichiometry.analyte_per_titrant() / self.titrant_concentration)
+            .into_same_unit_with(&Volume::milli_liter_from(0.0))
+    }
+    // 終点までの滴下量から試料の濃度を求める
+    pub fn get_analyte_concentration(&self, delivered: Volume) -> Concentration {
+        (self.titrant_concentration * delivered * self.stoichiometry.analyte_per_titrant()
+            / self.analyte_volume)
+            .convert_to_molar()
+            .normalized()
+    }
+}